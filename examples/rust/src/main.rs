@@ -1,7 +1,6 @@
 use postgres::{Client, NoTls};
 use std::error::Error;
 use upid::Upid;
-use uuid::Uuid;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut client = Client::connect(
@@ -18,8 +17,8 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let create_table = r#"
         CREATE TABLE test_upid (
-            id_upid TEXT NOT NULL,   -- passing string for upid type
-            id_uuid UUID NOT NULL,   -- passing uuid for uuid type
+            id_upid UPID NOT NULL,   -- passing upid (as uuid) for upid type
+            id_uuid UUID NOT NULL,   -- passing upid for uuid type
             id_text TEXT NOT NULL    -- passing string for text type
         );
     "#;
@@ -30,19 +29,14 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let query = r#"
         INSERT INTO test_upid (id_upid, id_uuid, id_text)
-        VALUES ($1, $2, $3)
-        RETURNING id_upid, id_uuid, id_text;
+        VALUES ($1::uuid, $2, $3)
+        RETURNING id_upid::uuid, id_uuid, id_text;
     "#;
-    for row in client.query(
-        query,
-        &[&id.to_string(), &Uuid::from(id), &id.to_string()],
-    )? {
-        let id_upid: String = row.get(0);
-        let id_uuid: Uuid = row.get(1);
+    for row in client.query(query, &[&id, &id, &id.to_string()])? {
+        let id_upid: Upid = row.get(0);
+        let id_uuid: Upid = row.get(1);
         let id_text: String = row.get(2);
-        println!(
-            "Inserted:\nid_upid={id_upid}\nid_uuid={id_uuid}\nid_text={id_text}",
-        );
+        println!("Inserted:\nid_upid={id_upid}\nid_uuid={id_uuid}\nid_text={id_text}",);
     }
 
     Ok(())
@@ -0,0 +1,199 @@
+//! # upid_duckdb
+//!
+//! `upid_duckdb` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! providing UPID generation and inspection as a DuckDB loadable extension,
+//! so analytics on a DuckDB mirror of the schema can work with the same IDs.
+//!
+//! Upids are passed around as `VARCHAR`, and the extension adds:
+//! - `gen_upid(prefix)`: a new Upid with the current time
+//! - `upid_to_uuid(upid)`: the Upid as a `UUID`
+//! - `upid_to_timestamp(upid)`: the time of the Upid as a `TIMESTAMP`
+
+use std::error::Error;
+
+use duckdb::core::{DataChunkHandle, FlatVector, Inserter, LogicalTypeHandle, LogicalTypeId};
+use duckdb::ffi::duckdb_string_t;
+use duckdb::types::DuckString;
+use duckdb::vscalar::{ScalarFunctionSignature, VScalar};
+use duckdb::vtab::arrow::WritableVector;
+use duckdb::{duckdb_entrypoint_c_api, Connection};
+use inner_upid::Upid as InnerUpid;
+
+/// Reads the rows of a `VARCHAR` column, with `None` for nulls
+unsafe fn read_strings(input: &mut DataChunkHandle, column: usize) -> Vec<Option<String>> {
+    let vector = input.flat_vector(column);
+    vector
+        .as_slice_with_len::<duckdb_string_t>(input.len())
+        .iter()
+        .enumerate()
+        .map(|(row, value)| {
+            (!vector.row_is_null(row as u64))
+                .then(|| DuckString::new(&mut { *value }).as_str().to_string())
+        })
+        .collect()
+}
+
+/// Parses each row of a `VARCHAR` column, with `None` for nulls
+unsafe fn read_upids(
+    input: &mut DataChunkHandle,
+    column: usize,
+) -> Result<Vec<Option<InnerUpid>>, Box<dyn Error>> {
+    read_strings(input, column)
+        .into_iter()
+        .map(|text| match text {
+            Some(text) => InnerUpid::from_string(&text)
+                .map(Some)
+                .map_err(|err| format!("invalid upid: \"{text}\": {err}").into()),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+/// Writes each row with `write`, or a null
+fn write_rows<T>(
+    output: &mut FlatVector<'_>,
+    rows: &[Option<T>],
+    mut write: impl FnMut(&mut FlatVector<'_>, usize, &T),
+) {
+    for (row, value) in rows.iter().enumerate() {
+        match value {
+            Some(value) => write(output, row, value),
+            None => output.set_null(row),
+        }
+    }
+}
+
+/// The Upid as DuckDB stores a `UUID`: a hugeint with the top bit flipped,
+/// so that they sort as unsigned
+fn to_uuid_hugeint(upid: &InnerUpid) -> i128 {
+    (upid.0 ^ (1 << 127)) as i128
+}
+
+/// The time of the Upid as a DuckDB `TIMESTAMP`, in microseconds since the Unix epoch
+fn to_timestamp_micros(upid: &InnerUpid) -> i64 {
+    upid.milliseconds() as i64 * 1000
+}
+
+fn signature(input: LogicalTypeId, output: LogicalTypeId) -> Vec<ScalarFunctionSignature> {
+    vec![ScalarFunctionSignature::exact(
+        vec![LogicalTypeHandle::from(input)],
+        LogicalTypeHandle::from(output),
+    )]
+}
+
+/// `gen_upid(prefix VARCHAR) -> VARCHAR`
+struct GenUpid;
+
+impl VScalar for GenUpid {
+    type State = ();
+
+    fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let prefixes = unsafe { read_strings(input, 0) };
+        let mut output = output.flat_vector();
+        write_rows(&mut output, &prefixes, |output, row, prefix| {
+            output.insert(row, InnerUpid::from_prefix(prefix).to_string().as_str())
+        });
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        signature(LogicalTypeId::Varchar, LogicalTypeId::Varchar)
+    }
+
+    fn volatile() -> bool {
+        true
+    }
+}
+
+/// `upid_to_uuid(upid VARCHAR) -> UUID`
+struct UpidToUuid;
+
+impl VScalar for UpidToUuid {
+    type State = ();
+
+    fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let upids = unsafe { read_upids(input, 0)? };
+        let mut output = output.flat_vector();
+        write_rows(&mut output, &upids, |output, row, upid| {
+            unsafe { output.as_mut_slice::<i128>()[row] = to_uuid_hugeint(upid) };
+        });
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        signature(LogicalTypeId::Varchar, LogicalTypeId::Uuid)
+    }
+}
+
+/// `upid_to_timestamp(upid VARCHAR) -> TIMESTAMP`
+struct UpidToTimestamp;
+
+impl VScalar for UpidToTimestamp {
+    type State = ();
+
+    fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let upids = unsafe { read_upids(input, 0)? };
+        let mut output = output.flat_vector();
+        write_rows(&mut output, &upids, |output, row, upid| {
+            unsafe { output.as_mut_slice::<i64>()[row] = to_timestamp_micros(upid) };
+        });
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        signature(LogicalTypeId::Varchar, LogicalTypeId::Timestamp)
+    }
+}
+
+#[duckdb_entrypoint_c_api()]
+pub fn extension_entrypoint(con: Connection) -> Result<(), Box<dyn Error>> {
+    con.register_scalar_function::<GenUpid>("gen_upid")?;
+    con.register_scalar_function::<UpidToUuid>("upid_to_uuid")?;
+    con.register_scalar_function::<UpidToTimestamp>("upid_to_timestamp")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn test_to_uuid_hugeint() {
+        let upid = InnerUpid::from_string(TEXT).unwrap();
+        assert_eq!(
+            to_uuid_hugeint(&upid),
+            0x81909bc6_0f93_7043_5c61_c99524d61576_u128 as i128
+        );
+
+        // the nil and max UUIDs are the ends of the hugeint range
+        assert_eq!(to_uuid_hugeint(&InnerUpid(0)), i128::MIN);
+        assert_eq!(to_uuid_hugeint(&InnerUpid(u128::MAX)), i128::MAX);
+    }
+
+    #[test]
+    fn test_uuid_hugeint_sorts() {
+        let low = InnerUpid(1 << 126);
+        let high = InnerUpid(1 << 127);
+        assert!(to_uuid_hugeint(&low) < to_uuid_hugeint(&high));
+    }
+
+    #[test]
+    fn test_to_timestamp_micros() {
+        let upid = InnerUpid::from_string(TEXT).unwrap();
+        assert_eq!(to_timestamp_micros(&upid), 1_720_600_366_848_000);
+    }
+}
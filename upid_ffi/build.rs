@@ -0,0 +1,17 @@
+use std::env;
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("failed to read cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_src(format!("{crate_dir}/src/lib.rs"))
+        .with_config(config)
+        .generate()
+        .expect("failed to generate C bindings")
+        .write_to_file(format!("{crate_dir}/include/upid.h"));
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,87 @@
+#ifndef UPID_H
+#define UPID_H
+
+/* Generated by cbindgen from upid_ffi/src/lib.rs, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Length of a string-encoded Upid, excluding the NUL terminator
+ */
+#define UPID_STRING_LEN 27
+
+/**
+ * Status codes returned by the fallible functions
+ */
+typedef enum upid_status {
+  UPID_STATUS_OK = 0,
+  UPID_STATUS_NULL_POINTER,
+  UPID_STATUS_INVALID_UTF8,
+  UPID_STATUS_INVALID_LENGTH,
+  UPID_STATUS_INVALID_CHAR,
+  UPID_STATUS_OVERFLOW,
+  UPID_STATUS_BUFFER_TOO_SMALL,
+  UPID_STATUS_UNSUPPORTED_VERSION,
+  UPID_STATUS_UNEXPECTED_PREFIX,
+  UPID_STATUS_INVALID,
+} upid_status;
+
+/**
+ * A Upid as 16 big-endian bytes
+ */
+typedef struct upid_t {
+  uint8_t bytes[16];
+} upid_t;
+
+/**
+ * Generates a new Upid with the given prefix and the current time.
+ *
+ * `prefix` must be a NUL-terminated string, and is handled in the same way
+ * as the Rust implementation (padded or clipped to four characters).
+ * A NULL or non-UTF-8 `prefix` is treated as the empty prefix.
+ *
+ * # Safety
+ * `prefix` must be NULL or point to a valid NUL-terminated string.
+ */
+struct upid_t upid_generate(const char *prefix);
+
+/**
+ * Parses a NUL-terminated string into `out`.
+ *
+ * Returns `UPID_STATUS_OK` on success, and leaves `out` untouched otherwise.
+ *
+ * # Safety
+ * `text` must point to a valid NUL-terminated string and `out` to a writable `upid_t`.
+ */
+enum upid_status upid_parse(const char *text, struct upid_t *out);
+
+/**
+ * Formats a Upid into `buf` as a NUL-terminated string.
+ *
+ * `len` is the size of `buf` and must be at least `UPID_STRING_LEN + 1`.
+ *
+ * # Safety
+ * `buf` must point to at least `len` writable bytes.
+ */
+enum upid_status upid_format(struct upid_t upid, char *buf, size_t len);
+
+/**
+ * Gets the timestamp of a Upid in milliseconds since the Unix epoch
+ */
+uint64_t upid_timestamp_ms(struct upid_t upid);
+
+/**
+ * Copies the prefix of a Upid into `buf` as a NUL-terminated string.
+ *
+ * `len` is the size of `buf` and must be at least 6 to fit any prefix.
+ *
+ * # Safety
+ * `buf` must point to at least `len` writable bytes.
+ */
+enum upid_status upid_prefix(struct upid_t upid, char *buf, size_t len);
+
+#endif  /* UPID_H */
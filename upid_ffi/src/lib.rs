@@ -0,0 +1,208 @@
+//! # upid_ffi
+//!
+//! `upid_ffi` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! exposing UPID generation and parsing through a C ABI, so C, C++ and
+//! other languages can embed the canonical implementation.
+//!
+//! The header `include/upid.h` is generated by cbindgen on each build.
+
+// Functions take raw pointers from C callers, and document their own safety requirements
+#![allow(clippy::missing_safety_doc)]
+
+use core::ffi::{c_char, CStr};
+use core::ptr;
+use core::slice;
+
+use inner_upid::{DecodeError, Upid as InnerUpid};
+
+/// Length of a string-encoded Upid, excluding the NUL terminator
+pub const UPID_STRING_LEN: usize = 27;
+
+/// A Upid as 16 big-endian bytes
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct upid_t {
+    pub bytes: [u8; 16],
+}
+
+/// Status codes returned by the fallible functions
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum upid_status {
+    Ok = 0,
+    NullPointer,
+    InvalidUtf8,
+    InvalidLength,
+    InvalidChar,
+    Overflow,
+    BufferTooSmall,
+    UnsupportedVersion,
+    UnexpectedPrefix,
+    Invalid,
+}
+
+impl From<DecodeError> for upid_status {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::InvalidLength => upid_status::InvalidLength,
+            DecodeError::InvalidChar => upid_status::InvalidChar,
+            DecodeError::Overflow => upid_status::Overflow,
+            DecodeError::UnsupportedVersion(_) => upid_status::UnsupportedVersion,
+            DecodeError::UnexpectedPrefix => upid_status::UnexpectedPrefix,
+            _ => upid_status::Invalid,
+        }
+    }
+}
+
+impl From<InnerUpid> for upid_t {
+    fn from(upid: InnerUpid) -> Self {
+        upid_t {
+            bytes: upid.to_bytes(),
+        }
+    }
+}
+
+impl From<upid_t> for InnerUpid {
+    fn from(upid: upid_t) -> Self {
+        InnerUpid::from_bytes(upid.bytes)
+    }
+}
+
+/// Generates a new Upid with the given prefix and the current time.
+///
+/// `prefix` must be a NUL-terminated string, and is handled in the same way
+/// as the Rust implementation (padded or clipped to four characters).
+/// A NULL or non-UTF-8 `prefix` is treated as the empty prefix.
+///
+/// # Safety
+/// `prefix` must be NULL or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn upid_generate(prefix: *const c_char) -> upid_t {
+    let prefix = if prefix.is_null() {
+        ""
+    } else {
+        CStr::from_ptr(prefix).to_str().unwrap_or("")
+    };
+    InnerUpid::from_prefix(prefix).into()
+}
+
+/// Parses a NUL-terminated string into `out`.
+///
+/// Returns `UPID_STATUS_OK` on success, and leaves `out` untouched otherwise.
+///
+/// # Safety
+/// `text` must point to a valid NUL-terminated string and `out` to a writable `upid_t`.
+#[no_mangle]
+pub unsafe extern "C" fn upid_parse(text: *const c_char, out: *mut upid_t) -> upid_status {
+    if text.is_null() || out.is_null() {
+        return upid_status::NullPointer;
+    }
+    let text = match CStr::from_ptr(text).to_str() {
+        Ok(text) => text,
+        Err(_) => return upid_status::InvalidUtf8,
+    };
+    match InnerUpid::from_string(text) {
+        Ok(upid) => {
+            *out = upid.into();
+            upid_status::Ok
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// Formats a Upid into `buf` as a NUL-terminated string.
+///
+/// `len` is the size of `buf` and must be at least `UPID_STRING_LEN + 1`.
+///
+/// # Safety
+/// `buf` must point to at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn upid_format(upid: upid_t, buf: *mut c_char, len: usize) -> upid_status {
+    if buf.is_null() {
+        return upid_status::NullPointer;
+    }
+    if len < UPID_STRING_LEN + 1 {
+        return upid_status::BufferTooSmall;
+    }
+    let text = InnerUpid::from(upid).to_string();
+    let buf = slice::from_raw_parts_mut(buf as *mut u8, len);
+    buf[..UPID_STRING_LEN].copy_from_slice(text.as_bytes());
+    buf[UPID_STRING_LEN] = 0;
+    upid_status::Ok
+}
+
+/// Gets the timestamp of a Upid in milliseconds since the Unix epoch
+#[no_mangle]
+pub extern "C" fn upid_timestamp_ms(upid: upid_t) -> u64 {
+    InnerUpid::from(upid).milliseconds()
+}
+
+/// Copies the prefix of a Upid into `buf` as a NUL-terminated string.
+///
+/// `len` is the size of `buf` and must be at least 6 to fit any prefix.
+///
+/// # Safety
+/// `buf` must point to at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn upid_prefix(upid: upid_t, buf: *mut c_char, len: usize) -> upid_status {
+    if buf.is_null() {
+        return upid_status::NullPointer;
+    }
+    let prefix = InnerUpid::from(upid).prefix();
+    if len < prefix.len() + 1 {
+        return upid_status::BufferTooSmall;
+    }
+    ptr::copy_nonoverlapping(prefix.as_ptr(), buf as *mut u8, prefix.len());
+    *buf.add(prefix.len()) = 0;
+    upid_status::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &CStr = c"user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn test_cycle() {
+        let mut upid = upid_t { bytes: [0; 16] };
+        let status = unsafe { upid_parse(TEXT.as_ptr(), &mut upid) };
+        assert_eq!(status, upid_status::Ok);
+
+        let mut buf = [0 as c_char; UPID_STRING_LEN + 1];
+        let status = unsafe { upid_format(upid, buf.as_mut_ptr(), buf.len()) };
+        assert_eq!(status, upid_status::Ok);
+        assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }, TEXT);
+    }
+
+    #[test]
+    fn test_generate() {
+        let upid = unsafe { upid_generate(c"user".as_ptr()) };
+        let mut buf = [0 as c_char; 5];
+        let status = unsafe { upid_prefix(upid, buf.as_mut_ptr(), buf.len()) };
+        assert_eq!(status, upid_status::Ok);
+        assert_eq!(unsafe { CStr::from_ptr(buf.as_ptr()) }, c"user");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut upid = upid_t { bytes: [0; 16] };
+        let status = unsafe { upid_parse(c"user_short".as_ptr(), &mut upid) };
+        assert_eq!(status, upid_status::InvalidLength);
+
+        let status = unsafe { upid_parse(ptr::null(), &mut upid) };
+        assert_eq!(status, upid_status::NullPointer);
+
+        let mut buf = [0 as c_char; 10];
+        let status = unsafe { upid_format(upid, buf.as_mut_ptr(), buf.len()) };
+        assert_eq!(status, upid_status::BufferTooSmall);
+    }
+
+    #[test]
+    fn test_timestamp_ms() {
+        let upid: upid_t = InnerUpid::from_prefix_and_milliseconds("user", 1720568902000).into();
+        assert!(1720568902000 - upid_timestamp_ms(upid) < 257);
+    }
+}
@@ -0,0 +1,123 @@
+//! # upid_node
+//!
+//! `upid_node` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! providing native Node.js bindings via napi-rs, for backend services
+//! that don't want to pay the cost of the wasm bridge.
+//!
+//! Invalid input is reported by throwing a JavaScript `Error`.
+
+use inner_upid::Upid;
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use uuid::Uuid;
+
+/// The decoded fields of a Upid
+#[napi(object)]
+pub struct UpidInfo {
+    pub prefix: String,
+    pub timestamp_ms: f64,
+    pub uuid: String,
+    pub hex: String,
+}
+
+fn parse_upid(text: &str) -> Result<Upid> {
+    Upid::from_string(text).map_err(|err| {
+        Error::new(
+            Status::InvalidArg,
+            format!("invalid upid: \"{text}\": {err}"),
+        )
+    })
+}
+
+/// Generates a new Upid with the provided prefix and the current time
+#[napi]
+pub fn generate(prefix: Option<String>) -> String {
+    Upid::from_prefix(prefix.as_deref().unwrap_or("")).to_string()
+}
+
+/// Parses a Upid string into its 16 big-endian bytes
+#[napi]
+pub fn parse(text: String) -> Result<Buffer> {
+    Ok(parse_upid(&text)?.to_bytes().to_vec().into())
+}
+
+/// Formats 16 big-endian bytes as a Upid string
+#[napi]
+pub fn format(bytes: Buffer) -> Result<String> {
+    let bytes: [u8; 16] = bytes.as_ref().try_into().map_err(|_| {
+        Error::new(
+            Status::InvalidArg,
+            format!("invalid upid: expected 16 bytes, got {}", bytes.len()),
+        )
+    })?;
+    Ok(Upid::from_bytes(bytes).to_string())
+}
+
+/// Returns true if the string is a valid Upid
+#[napi]
+pub fn is_valid(text: String) -> bool {
+    Upid::from_string(&text).is_ok()
+}
+
+/// Decodes a Upid string into its fields
+#[napi]
+pub fn inspect(text: String) -> Result<UpidInfo> {
+    let upid = parse_upid(&text)?;
+    Ok(UpidInfo {
+        prefix: upid.prefix(),
+        timestamp_ms: upid.milliseconds() as f64,
+        uuid: Uuid::from(upid).hyphenated().to_string(),
+        hex: format!("{:032x}", upid.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+    const UUID: &str = "01909bc6-0f93-7043-5c61-c99524d61576";
+
+    #[test]
+    fn test_generate() {
+        let text = generate(Some("user".to_string()));
+        assert_eq!(Upid::from_string(&text).unwrap().prefix(), "user");
+
+        let text = generate(None);
+        assert!(is_valid(text));
+    }
+
+    #[test]
+    fn test_parse() {
+        let bytes = parse(TEXT.to_string()).unwrap();
+        assert_eq!(bytes.as_ref(), Upid::from_string(TEXT).unwrap().to_bytes());
+        assert_eq!(format(bytes).unwrap(), TEXT);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let Err(err) = parse("user_short".to_string()) else {
+            panic!("parsed an invalid upid");
+        };
+        assert_eq!(err.status, Status::InvalidArg);
+        assert!(err.reason.starts_with("invalid upid: \"user_short\": "));
+
+        let err = format(vec![0; 15].into()).unwrap_err();
+        assert_eq!(err.reason, "invalid upid: expected 16 bytes, got 15");
+    }
+
+    #[test]
+    fn test_inspect() {
+        let info = inspect(TEXT.to_string()).unwrap();
+        assert_eq!(info.prefix, "user");
+        assert_eq!(info.timestamp_ms, 1720600366848.0);
+        assert_eq!(info.uuid, UUID);
+        assert_eq!(info.hex, UUID.replace('-', ""));
+
+        let Err(err) = inspect("user_short".to_string()) else {
+            panic!("inspected an invalid upid");
+        };
+        assert_eq!(err.status, Status::InvalidArg);
+    }
+}
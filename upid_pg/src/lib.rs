@@ -11,51 +11,122 @@ use inner_upid::Upid as InnerUpid;
 use pgrx::{
     pg_shmem_init,
     pg_sys::{Datum, Oid},
+    pgrx_sql_entity_graph::metadata::{
+        ArgumentError, Returns, ReturnsError, SqlMapping, SqlTranslatable,
+    },
     prelude::*,
     rust_regtypein,
     shmem::*,
-    PgLwLock, StringInfo, Uuid,
+    GucContext, GucFlags, GucRegistry, GucSetting, Internal, PgLwLock, PgMemoryContexts,
+    StringInfo, Uuid,
 };
 
 pgrx::pg_module_magic!();
 
 static SHARED_UPID: PgLwLock<u128> = PgLwLock::new();
 
+static DEFAULT_PREFIX: GucSetting<Option<&'static CStr>> =
+    GucSetting::<Option<&'static CStr>>::new(None);
+
 #[pg_guard]
 pub extern "C" fn _PG_init() {
     pg_shmem_init!(SHARED_UPID);
+    GucRegistry::define_string_guc(
+        "upid.default_prefix",
+        "Prefix used by gen_upid() without arguments",
+        "The prefix for Upids generated by gen_upid() when no prefix is passed.",
+        &DEFAULT_PREFIX,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
 }
 
 #[allow(non_camel_case_types)]
 #[derive(
-    PostgresType, PostgresEq, PostgresHash, PostgresOrd, Debug, PartialEq, PartialOrd, Eq, Hash, Ord,
+    PostgresEq, PostgresHash, PostgresOrd, Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Hash, Ord,
 )]
-#[inoutfuncs]
 pub struct upid(u128);
 
-impl InOutFuncs for upid {
-    #[inline]
-    fn input(input: &CStr) -> Self
-    where
-        Self: Sized,
-    {
-        let val = input.to_str().unwrap();
-        let inner = InnerUpid::from_string(val)
-            .unwrap_or_else(|err| panic!("invalid input syntax for type upid: \"{val}\": {err}"));
+// Stored as a fixed-length 16 bytes, big-endian like the built-in uuid type,
+// rather than as a varlena with a length header
+const UPID_LEN: usize = 16;
+
+extension_sql!(
+    r#"
+CREATE TYPE upid;
+"#,
+    name = "upid_shell",
+    creates = [Type(upid)]
+);
+
+#[pg_extern(immutable, parallel_safe, requires = ["upid_shell"])]
+fn upid_in(input: &CStr) -> upid {
+    let val = input.to_str().unwrap();
+    let inner = InnerUpid::from_string(val)
+        .unwrap_or_else(|err| panic!("invalid input syntax for type upid: \"{val}\": {err}"));
+
+    upid(inner.0)
+}
+
+#[pg_extern(immutable, parallel_safe, requires = ["upid_shell"])]
+fn upid_out(input: upid) -> &'static CStr {
+    let mut buffer = StringInfo::new();
+    buffer.push_str(&InnerUpid(input.0).to_string());
+    buffer.into()
+}
+
+// The binary wire format is the same 16 big-endian bytes, as for uuid
+#[pg_extern(immutable, parallel_safe, requires = ["upid_shell"])]
+fn upid_recv(internal: Internal) -> upid {
+    let buf = unsafe { internal.get_mut::<pg_sys::StringInfoData>() }
+        .expect("upid_recv requires a message buffer");
+
+    let mut bytes = [0u8; UPID_LEN];
+    unsafe { pg_sys::pq_copymsgbytes(buf, bytes.as_mut_ptr().cast(), UPID_LEN as i32) };
+    upid(u128::from_be_bytes(bytes))
+}
+
+#[pg_extern(immutable, parallel_safe, requires = ["upid_shell"])]
+fn upid_send(input: upid) -> Vec<u8> {
+    input.0.to_be_bytes().to_vec()
+}
+
+extension_sql!(
+    r#"
+CREATE TYPE upid (
+    INTERNALLENGTH = 16,
+    INPUT = upid_in,
+    OUTPUT = upid_out,
+    RECEIVE = upid_recv,
+    SEND = upid_send,
+    STORAGE = plain,
+    ALIGNMENT = char
+);
+"#,
+    name = "upid_type",
+    requires = ["upid_shell", upid_in, upid_out, upid_recv, upid_send]
+);
 
-        upid(inner.0)
+unsafe impl SqlTranslatable for upid {
+    fn argument_sql() -> Result<SqlMapping, ArgumentError> {
+        Ok(SqlMapping::As(String::from("upid")))
     }
 
-    #[inline]
-    fn output(&self, buffer: &mut StringInfo) {
-        buffer.push_str(&InnerUpid(self.0).to_string())
+    fn return_sql() -> Result<Returns, ReturnsError> {
+        Ok(Returns::One(SqlMapping::As(String::from("upid"))))
     }
 }
 
 impl IntoDatum for upid {
     #[inline]
     fn into_datum(self) -> Option<Datum> {
-        self.0.to_ne_bytes().into_datum()
+        let ptr = unsafe {
+            // SAFETY: CurrentMemoryContext is always valid
+            PgMemoryContexts::CurrentMemoryContext.palloc_slice::<u8>(UPID_LEN)
+        };
+        ptr.copy_from_slice(&self.0.to_be_bytes());
+
+        Some(ptr.as_ptr().into())
     }
 
     #[inline]
@@ -66,22 +137,55 @@ impl IntoDatum for upid {
 
 impl FromDatum for upid {
     #[inline]
-    unsafe fn from_polymorphic_datum(datum: Datum, is_null: bool, typoid: Oid) -> Option<Self>
+    unsafe fn from_polymorphic_datum(datum: Datum, is_null: bool, _typoid: Oid) -> Option<Self>
     where
         Self: Sized,
     {
-        let bytes: &[u8] = FromDatum::from_polymorphic_datum(datum, is_null, typoid)?;
+        if is_null {
+            return None;
+        }
 
-        let mut len_bytes = [0u8; 16];
-        len_bytes.copy_from_slice(bytes);
+        let bytes = std::slice::from_raw_parts(datum.cast_mut_ptr::<u8>() as *const u8, UPID_LEN);
+        let mut be_bytes = [0u8; UPID_LEN];
+        be_bytes.copy_from_slice(bytes);
 
-        Some(upid(u128::from_ne_bytes(len_bytes)))
+        Some(upid(u128::from_be_bytes(be_bytes)))
     }
 }
 
 #[pg_extern]
 fn gen_upid(prefix: &str) -> upid {
-    upid(InnerUpid::new(prefix).0)
+    upid(InnerUpid::from_prefix(prefix).0)
+}
+
+/// Like `gen_upid(prefix)`, with the prefix from the `upid.default_prefix` setting
+#[pg_extern(name = "gen_upid")]
+fn gen_upid_default() -> upid {
+    let prefix = DEFAULT_PREFIX
+        .get()
+        .map(|prefix| prefix.to_str().unwrap())
+        .unwrap_or_else(|| error!("upid.default_prefix is not set"));
+    gen_upid(prefix)
+}
+
+/// Like `gen_upid`, but strictly increasing across all backends
+///
+/// Within the same 256ms time bucket the last Upid's time and random bits are
+/// incremented instead of drawing new random bits. Needs `upid_pg` in
+/// `shared_preload_libraries` for the shared memory slot.
+#[pg_extern]
+fn gen_monotonic_upid(prefix: &str) -> upid {
+    // the prefix and version sit in the low 24 bits, and are left out of the ordering
+    let new = InnerUpid::from_prefix(prefix).0;
+    let mut last = SHARED_UPID.exclusive();
+    let next = if new >> 24 > *last >> 24 {
+        new
+    } else {
+        // a random overflow carries into the time bits, which still sorts after
+        (((*last >> 24) + 1) << 24) | (new & 0xff_ffff)
+    };
+    *last = next;
+    upid(next)
 }
 
 #[pg_extern(immutable, parallel_safe)]
@@ -106,10 +210,33 @@ fn upid_to_bytea(input: upid) -> Vec<u8> {
 }
 
 #[pg_extern(immutable, parallel_safe)]
-fn upid_to_prefix(input: upid) -> String {
+fn upid_prefix(input: upid) -> String {
     InnerUpid(input.0).prefix()
 }
 
+#[pg_extern(immutable, parallel_safe)]
+fn upid_to_prefix(input: upid) -> String {
+    upid_prefix(input)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn upid_version(input: upid) -> String {
+    InnerUpid(input.0).version().to_string()
+}
+
+/// The random bits between the timestamp and the prefix, as big-endian bytes
+///
+/// These are 8 bytes for versions `a` and `c`, and 7 to 11 for version `b`.
+#[pg_extern(immutable, parallel_safe)]
+fn upid_random(input: upid) -> Vec<u8> {
+    let inner = InnerUpid(input.0);
+    let bits = match inner.version() {
+        'b' => 81 - 5 * inner.prefix().len(),
+        _ => 64,
+    };
+    inner.random().to_be_bytes()[16 - bits.div_ceil(8)..].to_vec()
+}
+
 #[pg_extern(immutable, parallel_safe)]
 fn upid_to_timestamp(input: upid) -> Timestamp {
     let inner_seconds = (InnerUpid(input.0).milliseconds() as f64) / 1000.0;
@@ -175,6 +302,13 @@ mod tests {
         let _ = Spi::get_one::<upid>("SELECT '01GV5PA9EQG7D82Q3Y4PKBZSYU'::upid;");
     }
 
+    #[pg_test]
+    fn test_fixed_length() {
+        let result =
+            Spi::get_one::<i32>(&format!("SELECT pg_column_size('{TEXT}'::upid);")).unwrap();
+        assert_eq!(Some(16), result);
+    }
+
     #[pg_test]
     fn test_upid_to_timestamp() {
         let result = Spi::get_one::<&str>(&format!(
@@ -197,6 +331,48 @@ mod tests {
         assert_eq!(Some(BYTEA), result);
     }
 
+    #[pg_test]
+    fn test_upid_prefix() {
+        let result = Spi::get_one::<&str>(&format!("SELECT upid_prefix('{TEXT}'::upid);")).unwrap();
+        assert_eq!(Some("user"), result);
+
+        Spi::run(&format!(
+            "CREATE TABLE foo (id upid CHECK (upid_prefix(id) = 'user'));
+            INSERT INTO foo VALUES ('{TEXT}');"
+        ))
+        .unwrap();
+    }
+
+    #[pg_test]
+    fn test_upid_version() {
+        let result =
+            Spi::get_one::<&str>(&format!("SELECT upid_version('{TEXT}'::upid);")).unwrap();
+        assert_eq!(Some("a"), result);
+    }
+
+    #[pg_test]
+    fn test_upid_random() {
+        let result =
+            Spi::get_one::<&[u8]>(&format!("SELECT upid_random('{TEXT}'::upid);")).unwrap();
+        assert_eq!(Some(&BYTEA[5..13]), result);
+
+        // five prefix characters leave 56 random bits
+        let result =
+            Spi::get_one::<&[u8]>("SELECT upid_random('order_2acdrlkji33pssidmomeb'::upid);")
+                .unwrap();
+        assert_eq!(
+            Some(&[0x70, 0x43, 0x5c, 0x61, 0xc9, 0x95, 0x24][..]),
+            result
+        );
+    }
+
+    #[pg_test]
+    fn test_upid_send() {
+        let result = Spi::get_one::<&[u8]>(&format!("SELECT upid_send('{TEXT}'::upid);")).unwrap();
+
+        assert_eq!(Some(BYTEA), result);
+    }
+
     #[pg_test]
     fn test_uuid_to_upid() {
         let result = Spi::get_one::<upid>(&format!("SELECT '{UUID}'::uuid::upid;")).unwrap();
@@ -209,6 +385,32 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[pg_test]
+    fn test_generate_default_prefix() {
+        let result = Spi::get_one::<&str>(
+            "SET upid.default_prefix TO 'post'; SELECT upid_prefix(gen_upid());",
+        )
+        .unwrap();
+        assert_eq!(Some("post"), result);
+    }
+
+    #[pg_test]
+    #[should_panic = "upid.default_prefix is not set"]
+    fn test_generate_default_prefix_unset() {
+        let _ = Spi::get_one::<upid>("RESET upid.default_prefix; SELECT gen_upid();");
+    }
+
+    #[pg_test]
+    fn test_generate_monotonic() {
+        let first = Spi::get_one::<upid>("SELECT gen_monotonic_upid('user');").unwrap();
+        let second = Spi::get_one::<upid>("SELECT gen_monotonic_upid('user');").unwrap();
+        assert!(first < second);
+
+        let result =
+            Spi::get_one::<&str>("SELECT upid_to_prefix(gen_monotonic_upid('post'));").unwrap();
+        assert_eq!(Some("post"), result);
+    }
+
     #[pg_test]
     fn test_hash() {
         Spi::run(
@@ -262,6 +464,6 @@ pub mod pg_test {
 
     pub fn postgresql_conf_options() -> Vec<&'static str> {
         // return any postgresql.conf settings that are required for your tests
-        vec![]
+        vec!["shared_preload_libraries = 'upid_pg'"]
     }
 }
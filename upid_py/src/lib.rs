@@ -0,0 +1,163 @@
+//! # upid_py
+//!
+//! `upid_py` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! providing the Rust implementation to Python via PyO3, so that Python
+//! services share exactly the same code as the backend.
+//!
+//! The API mirrors the pure-Python `upid` package.
+
+// False positive from the code generated by #[pymethods] for PyResult returns
+#![allow(clippy::useless_conversion)]
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use inner_upid::Upid as InnerUpid;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{timezone_utc_bound, IntoPyDict, PyBytes, PyDateTime};
+
+#[pyclass(name = "UPID", module = "upid_py", frozen)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Upid(InnerUpid);
+
+#[pymethods]
+impl Upid {
+    /// Create a new `UPID` from a `prefix`, using the current datetime.
+    #[new]
+    fn new(prefix: &str) -> Self {
+        Upid(InnerUpid::from_prefix(prefix))
+    }
+
+    /// Create a new `UPID` from a `prefix`, using the supplied `milliseconds`.
+    #[staticmethod]
+    fn from_prefix_and_milliseconds(prefix: &str, milliseconds: u64) -> Self {
+        Upid(InnerUpid::from_prefix_and_milliseconds(
+            prefix,
+            milliseconds.into(),
+        ))
+    }
+
+    /// Convert the provided `str` to a `UPID`, raising a `ValueError` if invalid.
+    #[staticmethod]
+    fn from_str(string: &str) -> PyResult<Self> {
+        InnerUpid::from_string(string)
+            .map(Upid)
+            .map_err(|err| PyValueError::new_err(format!("invalid upid: \"{string}\": {err}")))
+    }
+
+    /// Return just the prefix as a `str`.
+    #[getter]
+    fn prefix(&self) -> String {
+        self.0.prefix()
+    }
+
+    /// Returns a time in integer milliseconds since the epoch.
+    #[getter]
+    fn milliseconds(&self) -> u64 {
+        self.0.milliseconds()
+    }
+
+    #[getter]
+    fn datetime<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDateTime>> {
+        let seconds = self.0.milliseconds() as f64 / 1000.0;
+        PyDateTime::from_timestamp_bound(py, seconds, Some(&timezone_utc_bound(py)))
+    }
+
+    #[getter]
+    fn hex(&self) -> String {
+        format!("{:032x}", self.0 .0)
+    }
+
+    fn to_str(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Convert to a standard Python UUID.
+    fn to_uuid<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let bytes = PyBytes::new_bound(py, &self.0.to_bytes());
+        py.import_bound("uuid")?
+            .getattr("UUID")?
+            .call((), Some(&[("bytes", bytes)].into_py_dict_bound(py)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UPID({})", self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __int__(&self) -> u128 {
+        self.0 .0
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    fn __richcmp__(&self, other: PyRef<'_, Upid>, op: CompareOp) -> bool {
+        op.matches(self.0.cmp(&other.0))
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Generate a UPID with the provided prefix.
+#[pyfunction]
+fn upid(prefix: &str) -> Upid {
+    Upid::new(prefix)
+}
+
+#[pymodule]
+fn upid_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Upid>()?;
+    m.add_function(wrap_pyfunction!(upid, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+    const UUID: &str = "01909bc6-0f93-7043-5c61-c99524d61576";
+
+    #[test]
+    fn test_cycle() {
+        let upid = Upid::from_str(TEXT).unwrap();
+        assert_eq!(upid.to_str(), TEXT);
+        assert_eq!(upid.prefix(), "user");
+    }
+
+    #[test]
+    fn test_invalid() {
+        Python::with_gil(|_| assert!(Upid::from_str("user_short").is_err()));
+    }
+
+    #[test]
+    fn test_to_uuid() {
+        Python::with_gil(|py| {
+            let upid = Upid::from_str(TEXT).unwrap();
+            let uuid = upid.to_uuid(py).unwrap();
+            assert_eq!(uuid.str().unwrap().to_string(), UUID);
+        });
+    }
+
+    #[test]
+    fn test_compare() {
+        Python::with_gil(|py| {
+            let a = Bound::new(py, Upid::from_prefix_and_milliseconds("user", 0)).unwrap();
+            let b = Bound::new(py, Upid::from_prefix_and_milliseconds("user", 1 << 20)).unwrap();
+            assert!(a.lt(&b).unwrap());
+            assert!(a.eq(&a).unwrap());
+            assert!(!a.eq(&b).unwrap());
+        });
+    }
+}
@@ -0,0 +1,57 @@
+//! Benchmarks for generating, encoding and decoding Upids, alongside UUIDv4 and ULID.
+//!
+//! Run with `cargo bench`, and compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` then `cargo bench -- --baseline main`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ulid::Ulid;
+use upid::{base32, Upid};
+use uuid::Uuid;
+
+const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+fn generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("upid", |b| b.iter(|| Upid::new(black_box("user"))));
+    group.bench_function("upid_v2", |b| b.iter(|| Upid::new_v2(black_box("user"))));
+    group.bench_function("uuid_v4", |b| b.iter(Uuid::new_v4));
+    group.bench_function("ulid", |b| b.iter(Ulid::new));
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let upid = Upid::from_string(TEXT).unwrap();
+    let uuid = Uuid::from_bytes(upid.to_bytes());
+    let ulid = Ulid::from(upid.0);
+
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("upid", |b| b.iter(|| black_box(upid).to_string()));
+    group.bench_function("upid_encode_to", |b| {
+        let mut buffer = [0; base32::STR_LEN];
+        b.iter(|| black_box(upid).encode_to(&mut buffer).len())
+    });
+    group.bench_function("uuid", |b| b.iter(|| black_box(uuid).to_string()));
+    group.bench_function("ulid", |b| b.iter(|| black_box(ulid).to_string()));
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let upid = Upid::from_string(TEXT).unwrap();
+    let uuid = Uuid::from_bytes(upid.to_bytes()).to_string();
+    let ulid = Ulid::from(upid.0).to_string();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("upid", |b| b.iter(|| Upid::from_string(black_box(TEXT))));
+    group.bench_function("upid_validate", |b| {
+        b.iter(|| Upid::validate(black_box(TEXT)))
+    });
+    group.bench_function("uuid", |b| b.iter(|| Uuid::parse_str(black_box(&uuid))));
+    group.bench_function("ulid", |b| b.iter(|| Ulid::from_string(black_box(&ulid))));
+    group.finish();
+}
+
+criterion_group!(benches, generate, encode, decode);
+criterion_main!(benches);
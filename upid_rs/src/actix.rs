@@ -0,0 +1,215 @@
+//! Extract Upids from actix-web path parameters.
+//!
+//! [`Upid`] and [`TypedUpid`] can be used directly as handler arguments
+//! for routes with a single path parameter, mirroring the axum integration.
+//! Anything that doesn't parse is rejected with a `400 Bad Request`,
+//! which can be changed by registering a [`UpidConfig`] as app data.
+//!
+//! ```rust
+//! use actix_web::{web, App};
+//! use upid::Upid;
+//!
+//! async fn show_user(id: Upid) -> String {
+//!     id.prefix()
+//! }
+//!
+//! let app = App::new().route("/users/{id}", web::get().to(show_user));
+//! ```
+
+use std::fmt;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, ResponseError};
+
+use crate::{DecodeError, TypedUpid, Upid, UpidPrefix};
+
+/// Error used when a Upid can't be extracted from the path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpidError {
+    /// The route has more or less than one path parameter
+    ParamCount(usize),
+    /// The path parameter isn't a valid Upid
+    Decode(DecodeError),
+}
+
+impl fmt::Display for UpidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpidError::ParamCount(count) => {
+                write!(f, "expected a single path parameter, got {}", count)
+            }
+            UpidError::Decode(err) => write!(f, "invalid upid: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for UpidError {}
+
+impl ResponseError for UpidError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            // the route is set up wrong, so it isn't the client's fault
+            UpidError::ParamCount(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            UpidError::Decode(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+}
+
+type ErrorHandler = dyn Fn(UpidError, &HttpRequest) -> actix_web::Error + Send + Sync;
+
+/// Configures how Upid extraction errors are turned into responses
+///
+/// # Example
+/// ```rust
+/// use actix_web::{error, App};
+/// use upid::actix::UpidConfig;
+///
+/// let app = App::new().app_data(
+///     UpidConfig::default().error_handler(|err, _req| error::ErrorNotFound(err)),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct UpidConfig {
+    error_handler: Option<Arc<ErrorHandler>>,
+}
+
+impl UpidConfig {
+    /// Sets a custom handler that turns extraction errors into responses
+    pub fn error_handler<F>(mut self, f: F) -> UpidConfig
+    where
+        F: Fn(UpidError, &HttpRequest) -> actix_web::Error + Send + Sync + 'static,
+    {
+        self.error_handler = Some(Arc::new(f));
+        self
+    }
+}
+
+fn extract(req: &HttpRequest) -> Result<Upid, actix_web::Error> {
+    let params: Vec<_> = req.match_info().iter().collect();
+    let result = match params[..] {
+        [(_, value)] => Upid::from_string(value).map_err(UpidError::Decode),
+        ref other => Err(UpidError::ParamCount(other.len())),
+    };
+    result.map_err(|err| handle(err, req))
+}
+
+fn handle(err: UpidError, req: &HttpRequest) -> actix_web::Error {
+    match req
+        .app_data::<UpidConfig>()
+        .and_then(|c| c.error_handler.as_ref())
+    {
+        Some(handler) => handler(err, req),
+        None => err.into(),
+    }
+}
+
+impl FromRequest for Upid {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(extract(req))
+    }
+}
+
+impl<P: UpidPrefix> FromRequest for TypedUpid<P> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(extract(req).and_then(|upid| {
+            TypedUpid::from_upid(upid).map_err(|err| handle(UpidError::Decode(err), req))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::{error, test, web, App};
+
+    struct User;
+
+    impl UpidPrefix for User {
+        const PREFIX: &'static str = "user";
+    }
+
+    async fn get_path(config: UpidConfig, path: &str) -> (StatusCode, String) {
+        let app = test::init_service(
+            App::new()
+                .app_data(config)
+                .route(
+                    "/upids/{id}",
+                    web::get().to(|id: Upid| async move { id.to_string() }),
+                )
+                .route(
+                    "/users/{id}",
+                    web::get().to(|id: TypedUpid<User>| async move { id.to_string() }),
+                )
+                .route(
+                    "/{a}/{b}",
+                    web::get().to(|id: Upid| async move { id.to_string() }),
+                ),
+        )
+        .await;
+        let request = test::TestRequest::get().uri(path).to_request();
+        let response = test::call_service(&app, request).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[actix_rt::test]
+    async fn extracts_upid() {
+        let text = "user_aaccvpp5guht4dts56je5a";
+        assert_eq!(
+            get_path(UpidConfig::default(), &format!("/upids/{text}")).await,
+            (StatusCode::OK, text.to_string())
+        );
+        assert_eq!(
+            get_path(UpidConfig::default(), &format!("/users/{text}")).await,
+            (StatusCode::OK, text.to_string())
+        );
+    }
+
+    #[actix_rt::test]
+    async fn rejects_invalid() {
+        assert_eq!(
+            get_path(UpidConfig::default(), "/upids/nope").await,
+            (
+                StatusCode::BAD_REQUEST,
+                "invalid upid: invalid length".into()
+            )
+        );
+        assert_eq!(
+            get_path(UpidConfig::default(), "/users/ordr_aaccvpp5guht4dts56je5a").await,
+            (
+                StatusCode::BAD_REQUEST,
+                "invalid upid: unexpected prefix".into()
+            )
+        );
+        assert_eq!(
+            get_path(UpidConfig::default(), "/x/y").await,
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "expected a single path parameter, got 2".into()
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn uses_error_handler() {
+        let config = UpidConfig::default().error_handler(|err, _| error::ErrorNotFound(err));
+        assert_eq!(
+            get_path(config, "/upids/nope").await,
+            (StatusCode::NOT_FOUND, "invalid upid: invalid length".into())
+        );
+    }
+}
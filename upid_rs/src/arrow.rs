@@ -0,0 +1,175 @@
+//! Convert columns of Upids to and from Arrow arrays.
+//!
+//! The binary form is a `FixedSizeBinaryArray` of width 16 holding the big-endian bytes,
+//! which sorts by time and avoids any string handling. These functions work a whole
+//! column at a time and only allocate the output buffers.
+
+use std::fmt;
+
+use arrow_array::builder::StringBuilder;
+use arrow_array::{Array, FixedSizeBinaryArray, StringArray};
+use arrow_buffer::{Buffer, NullBuffer};
+use arrow_schema::ArrowError;
+
+use crate::{b32, DecodeError, Upid};
+
+/// Width of each value in the binary form
+const WIDTH: i32 = 16;
+
+/// A decode error in one row of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowError {
+    /// The index of the row in the column
+    pub row: usize,
+    /// Why the row isn't a valid Upid
+    pub error: DecodeError,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.error)
+    }
+}
+
+impl std::error::Error for RowError {}
+
+/// Converts Upids to a `FixedSizeBinaryArray` of their 16 bytes
+pub fn to_binary_array(upids: &[Upid]) -> FixedSizeBinaryArray {
+    let mut values = Vec::with_capacity(upids.len() * WIDTH as usize);
+    for upid in upids {
+        values.extend_from_slice(&upid.to_bytes());
+    }
+    FixedSizeBinaryArray::new(WIDTH, Buffer::from_vec(values), None)
+}
+
+/// Reads Upids from a `FixedSizeBinaryArray`, with nulls as `None`
+///
+/// Fails if the array's values aren't 16 bytes wide.
+pub fn from_binary_array(array: &FixedSizeBinaryArray) -> Result<Vec<Option<Upid>>, ArrowError> {
+    if array.value_length() != WIDTH {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "expected upids to be {} bytes wide, got {}",
+            WIDTH,
+            array.value_length()
+        )));
+    }
+    Ok(array
+        .iter()
+        .map(|value| value.map(|bytes| Upid::from_slice(bytes).expect("checked width")))
+        .collect())
+}
+
+/// Converts Upids to a `StringArray` of their string form
+pub fn to_string_array(upids: &[Upid]) -> StringArray {
+    let mut builder = StringBuilder::with_capacity(upids.len(), upids.len() * b32::STR_LEN);
+    let mut buffer = [0; b32::STR_LEN];
+    for upid in upids {
+        b32::encode_into(upid.0, &mut buffer);
+        builder
+            .append_value(std::str::from_utf8(&buffer).expect("base32 alphabet is always ASCII"));
+    }
+    builder.finish()
+}
+
+/// Parses Upids from a `StringArray`, with nulls as `None`
+///
+/// Stops at the first invalid row. Use [`decode_string_array`] to carry on past them.
+pub fn from_string_array(array: &StringArray) -> Result<Vec<Option<Upid>>, RowError> {
+    array
+        .iter()
+        .enumerate()
+        .map(|(row, value)| {
+            value
+                .map(|text| Upid::from_string(text).map_err(|error| RowError { row, error }))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Parses a `StringArray` straight into the binary form, reporting every invalid row
+///
+/// Invalid rows are null in the output, as are rows that were null to begin with.
+pub fn decode_string_array(array: &StringArray) -> (FixedSizeBinaryArray, Vec<RowError>) {
+    let mut values = Vec::with_capacity(array.len() * WIDTH as usize);
+    let mut valid = Vec::with_capacity(array.len());
+    let mut errors = Vec::new();
+    for (row, value) in array.iter().enumerate() {
+        let upid = match value.map(Upid::from_string) {
+            Some(Ok(upid)) => Some(upid),
+            Some(Err(error)) => {
+                errors.push(RowError { row, error });
+                None
+            }
+            None => None,
+        };
+        values.extend_from_slice(&upid.unwrap_or(Upid(0)).to_bytes());
+        valid.push(upid.is_some());
+    }
+    let nulls = Some(NullBuffer::from(valid)).filter(|nulls| nulls.null_count() > 0);
+    let array = FixedSizeBinaryArray::new(WIDTH, Buffer::from_vec(values), nulls);
+    (array, errors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binary_cycle() {
+        let upids = vec![Upid::new("user"), Upid::new("ordr"), Upid::new_v2("tx")];
+        let array = to_binary_array(&upids);
+
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.value(1), upids[1].to_bytes());
+        let got: Vec<Upid> = from_binary_array(&array)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(got, upids);
+    }
+
+    #[test]
+    fn binary_wrong_width() {
+        let array = FixedSizeBinaryArray::new(8, Buffer::from_vec(vec![0u8; 16]), None);
+        assert!(from_binary_array(&array).is_err());
+    }
+
+    #[test]
+    fn string_cycle() {
+        let upids = vec![Upid::new("user"), Upid::new("ordr"), Upid::new_v2("tx")];
+        let array = to_string_array(&upids);
+
+        assert_eq!(array.value(0), upids[0].to_string());
+        let got: Vec<Upid> = from_string_array(&array)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(got, upids);
+    }
+
+    #[test]
+    fn string_errors() {
+        let upid = Upid::new("user");
+        let text = upid.to_string();
+        let array = StringArray::from(vec![Some(text.as_str()), None, Some("nope"), Some("")]);
+
+        assert_eq!(
+            from_string_array(&array),
+            Err(RowError {
+                row: 2,
+                error: DecodeError::InvalidLength
+            })
+        );
+
+        let (binary, errors) = decode_string_array(&array);
+        assert_eq!(binary.len(), 4);
+        assert_eq!(binary.null_count(), 3);
+        assert_eq!(
+            from_binary_array(&binary).unwrap(),
+            vec![Some(upid), None, None, None]
+        );
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2, 3]);
+    }
+}
@@ -0,0 +1,65 @@
+//! Use Upid as a GraphQL scalar with `async-graphql`.
+//!
+//! It is sent and received as its usual string form,
+//! and strings that aren't valid Upids are rejected as input errors.
+
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+
+use crate::Upid;
+
+#[Scalar(name = "Upid")]
+impl ScalarType for Upid {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::String(s) => Ok(Upid::from_string(s)?),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn echo(&self, id: Upid) -> Upid {
+            id
+        }
+    }
+
+    async fn execute(query: &str) -> async_graphql::Response {
+        Schema::new(Query, EmptyMutation, EmptySubscription)
+            .execute(query)
+            .await
+    }
+
+    #[tokio::test]
+    async fn round_trips() {
+        let text = "user_aaccvpp5guht4dts56je5a";
+        let response = execute(&format!("{{ echo(id: \"{text}\") }}")).await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "echo": text })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid() {
+        let response = execute("{ echo(id: \"nope\") }").await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("invalid length"));
+
+        let response = execute("{ echo(id: 123) }").await;
+        assert_eq!(response.errors.len(), 1);
+    }
+}
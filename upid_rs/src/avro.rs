@@ -0,0 +1,75 @@
+//! Store Upids in Avro as a `fixed(16)` with the logical type `upid`.
+//!
+//! A Upid's Avro encoding is simply its 16 big-endian bytes, so this doesn't
+//! depend on an Avro library. With `apache-avro`, use
+//! `Value::Fixed(16, upid.to_avro_fixed().to_vec())` to write and
+//! [`Upid::from_avro_fixed`] on the bytes of a `Value::Fixed` to read.
+//!
+//! Readers that don't know the `upid` logical type fall back to the plain
+//! `fixed`, as the Avro spec requires, so the schema stays compatible.
+
+use crate::{DecodeError, Upid};
+
+/// The logical type name written into schemas
+pub const LOGICAL_TYPE: &str = "upid";
+
+/// Size of the Avro `fixed` type, in bytes
+pub const FIXED_SIZE: usize = 16;
+
+/// A schema for a Upid, to put in a record field's `type`
+///
+/// Avro named types must only be defined once per schema, so later fields
+/// should refer to it by its full name, `upid.Upid`.
+pub const SCHEMA: &str =
+    r#"{"type":"fixed","name":"Upid","namespace":"upid","size":16,"logicalType":"upid"}"#;
+
+impl Upid {
+    /// Gets the Avro `fixed(16)` encoding of this Upid
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::new("user");
+    /// let fixed = upid.to_avro_fixed();
+    ///
+    /// assert_eq!(Upid::from_avro_fixed(&fixed), Ok(upid));
+    /// ```
+    pub const fn to_avro_fixed(&self) -> [u8; FIXED_SIZE] {
+        self.to_bytes()
+    }
+
+    /// Creates a Upid from the bytes of an Avro `fixed(16)`
+    ///
+    /// Returns `DecodeError::InvalidLength` if there aren't exactly 16 bytes.
+    pub fn from_avro_fixed(bytes: &[u8]) -> Result<Upid, DecodeError> {
+        Upid::from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn avro_cycle() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let fixed = upid.to_avro_fixed();
+
+        assert_eq!(fixed, upid.to_bytes());
+        assert_eq!(Upid::from_avro_fixed(&fixed), Ok(upid));
+        assert_eq!(
+            Upid::from_avro_fixed(&fixed[..15]),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn avro_schema() {
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+
+        assert_eq!(schema["type"], "fixed");
+        assert_eq!(schema["size"], FIXED_SIZE);
+        assert_eq!(schema["logicalType"], LOGICAL_TYPE);
+    }
+}
@@ -0,0 +1,154 @@
+//! Extract Upids from axum path parameters.
+//!
+//! [`Upid`] and [`TypedUpid`] can be used directly as handler arguments
+//! for routes with a single path parameter. Anything that doesn't parse
+//! is rejected with a `400 Bad Request` and the decode error as the body.
+//!
+//! ```rust
+//! use axum::{routing::get, Router};
+//! use upid::Upid;
+//!
+//! async fn show_user(id: Upid) -> String {
+//!     id.prefix()
+//! }
+//!
+//! let app: Router = Router::new().route("/users/{id}", get(show_user));
+//! ```
+
+use std::fmt;
+
+use axum::extract::rejection::RawPathParamsRejection;
+use axum::extract::{FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use crate::{DecodeError, TypedUpid, Upid, UpidPrefix};
+
+/// Rejection used when a Upid can't be extracted from the path
+#[derive(Debug)]
+pub enum UpidRejection {
+    /// The path parameters couldn't be read at all
+    Path(RawPathParamsRejection),
+    /// The route has more or less than one path parameter
+    ParamCount(usize),
+    /// The path parameter isn't a valid Upid
+    Decode(DecodeError),
+}
+
+impl fmt::Display for UpidRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpidRejection::Path(rejection) => write!(f, "{}", rejection),
+            UpidRejection::ParamCount(count) => {
+                write!(f, "expected a single path parameter, got {}", count)
+            }
+            UpidRejection::Decode(err) => write!(f, "invalid upid: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for UpidRejection {}
+
+impl IntoResponse for UpidRejection {
+    fn into_response(self) -> Response {
+        match self {
+            UpidRejection::Path(rejection) => rejection.into_response(),
+            // the route is set up wrong, so it isn't the client's fault
+            UpidRejection::ParamCount(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+            }
+            UpidRejection::Decode(_) => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Upid {
+    type Rejection = UpidRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(UpidRejection::Path)?;
+        match params.iter().collect::<Vec<_>>()[..] {
+            [(_, value)] => Upid::from_string(value).map_err(UpidRejection::Decode),
+            ref other => Err(UpidRejection::ParamCount(other.len())),
+        }
+    }
+}
+
+impl<S: Send + Sync, P: UpidPrefix> FromRequestParts<S> for TypedUpid<P> {
+    type Rejection = UpidRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let upid = Upid::from_request_parts(parts, state).await?;
+        TypedUpid::from_upid(upid).map_err(UpidRejection::Decode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::get;
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    struct User;
+
+    impl UpidPrefix for User {
+        const PREFIX: &'static str = "user";
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/upids/{id}", get(|id: Upid| async move { id.to_string() }))
+            .route(
+                "/users/{id}",
+                get(|id: TypedUpid<User>| async move { id.to_string() }),
+            )
+            .route("/{a}/{b}", get(|id: Upid| async move { id.to_string() }))
+    }
+
+    async fn get_path(path: &str) -> (StatusCode, String) {
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn extracts_upid() {
+        let text = "user_aaccvpp5guht4dts56je5a";
+        assert_eq!(
+            get_path(&format!("/upids/{text}")).await,
+            (StatusCode::OK, text.to_string())
+        );
+        assert_eq!(
+            get_path(&format!("/users/{text}")).await,
+            (StatusCode::OK, text.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid() {
+        assert_eq!(
+            get_path("/upids/nope").await,
+            (
+                StatusCode::BAD_REQUEST,
+                "invalid upid: invalid length".into()
+            )
+        );
+        assert_eq!(
+            get_path("/users/ordr_aaccvpp5guht4dts56je5a").await,
+            (
+                StatusCode::BAD_REQUEST,
+                "invalid upid: unexpected prefix".into()
+            )
+        );
+        assert_eq!(get_path("/x/y").await.0, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
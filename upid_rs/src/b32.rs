@@ -16,6 +16,23 @@ const VERSION_CHAR_LEN: usize = 1;
 /// Length of a string-encoded Upid
 const CHAR_LEN: usize = 26;
 
+// Version 2 trades random bits for a prefix of 0 to 5 characters, keeping the same length.
+// The binary order is TIMESTAMP_RANDO_PREFIX_LENGTH_VERSION
+// and the string order is PREFIX_TIME_RANDO+LENGTH_VERSION
+/// Version bits (and base32 index) of the fixed four-character prefix layout, ie 'a'
+pub const VERSION_V1: u8 = 6;
+/// Version bits (and base32 index) of the variable-length prefix layout, ie 'b'
+pub const VERSION_V2: u8 = 7;
+/// Version bits (and base32 index) of the custom epoch layout, ie 'c'
+///
+/// The layout is the same as version 1, but the time counts from a later epoch.
+pub const VERSION_V3: u8 = 8;
+/// Longest prefix that the version 2 constructors will encode
+pub const MAX_PREFIX_LEN_V2: usize = 5;
+const TIME_SHIFT: usize = 88;
+const LENGTH_SHIFT_V2: usize = 4;
+const PREFIX_SHIFT_V2: usize = 7;
+
 /// 32-character alphabet modified from Crockford's
 /// Numbers first for sensible sorting, but full lower-case
 /// latin alphabet so any sensible prefix can be used
@@ -24,7 +41,7 @@ pub const ENCODE: &[u8; 32] = b"234567abcdefghijklmnopqrstuvwxyz";
 
 /// Speedy O(1) inverse lookup
 /// base32 char -> ascii byte int -> base32 alphabet index
-const DECODE: [u8; 256] = [
+pub const DECODE: [u8; 256] = [
     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
     255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 1, 2, 3, 4, 5, 255, 255, 255,
@@ -41,21 +58,95 @@ const DECODE: [u8; 256] = [
     255,
 ];
 
+/// Length of a string-encoded Upid including the `_` separator
+pub const STR_LEN: usize = CHAR_LEN + 1;
+
 /// Encodes the provided binary data to a base32 String
 pub fn encode(binary: u128) -> String {
+    let mut buffer = [0; STR_LEN];
+    encode_into(binary, &mut buffer);
+    String::from_utf8(buffer.to_vec()).expect("unexpected failure in base32 encode for upid")
+}
+
+/// Encodes the provided binary data into a buffer of base32 characters
+///
+/// The output is always ASCII, in the order PREFIX_TIME_RANDO_VERSION,
+/// and does not allocate.
+pub fn encode_into(binary: u128, buffer: &mut [u8; STR_LEN]) {
+    if is_v2(binary) {
+        return encode_v2_into(binary, buffer);
+    }
     let bytes: [u8; 16] = binary.to_be_bytes();
-    let time = encode_time(&bytes[0..TIME_BIN_LEN]);
-    let rando = encode_rando(&bytes[TIME_BIN_LEN..END_RANDO_BIN]);
-    let (prefix, version) = encode_prefix(&bytes[END_RANDO_BIN..]);
-    let out = format!("{}_{}{}{}", prefix, time, rando, version);
-    out
+    let (prefix, version) = encode_prefix_chars(&bytes[END_RANDO_BIN..]);
+    buffer[..PREFIX_CHAR_LEN].copy_from_slice(&prefix);
+    buffer[PREFIX_CHAR_LEN] = b'_';
+    let rest = &mut buffer[PREFIX_CHAR_LEN + 1..];
+    rest[..TIME_CHAR_LEN].copy_from_slice(&encode_time(&bytes[0..TIME_BIN_LEN]));
+    rest[TIME_CHAR_LEN..TIME_CHAR_LEN + RANDO_CHAR_LEN]
+        .copy_from_slice(&encode_rando(&bytes[TIME_BIN_LEN..END_RANDO_BIN]));
+    rest[TIME_CHAR_LEN + RANDO_CHAR_LEN..].copy_from_slice(&version);
+}
+
+/// Encodes the binary with the time characters first, and without the `_`
+///
+/// The order is TIME_PREFIX_RANDO_VERSION, so the strings sort by time.
+pub fn encode_sortable(binary: u128) -> [u8; CHAR_LEN] {
+    let mut canonical = [0; STR_LEN];
+    encode_into(binary, &mut canonical);
+    let len = canonical
+        .iter()
+        .position(|&b| b == b'_')
+        .expect("encoded upid always has a '_'");
+    let time = len + 1..len + 1 + TIME_CHAR_LEN;
+
+    let mut buffer = [0; CHAR_LEN];
+    buffer[..TIME_CHAR_LEN].copy_from_slice(&canonical[time.clone()]);
+    buffer[TIME_CHAR_LEN..TIME_CHAR_LEN + len].copy_from_slice(&canonical[..len]);
+    buffer[TIME_CHAR_LEN + len..].copy_from_slice(&canonical[time.end..]);
+    buffer
+}
+
+/// Decodes the output of [`encode_sortable`] into binary
+pub fn decode_sortable(encoded: &str) -> Result<u128, DecodeError> {
+    let bytes = encoded.as_bytes();
+    if bytes.len() != CHAR_LEN {
+        return Err(DecodeError::InvalidLength);
+    }
+    if bytes.iter().any(|&b| DECODE[b as usize] == 255) {
+        return Err(DecodeError::InvalidChar);
+    }
+
+    // the prefix length is needed to find the end of the prefix
+    let len = if bytes[CHAR_LEN - 1] == ENCODE[VERSION_V2 as usize] {
+        ((DECODE[bytes[CHAR_LEN - 2] as usize] >> 1) & 7) as usize
+    } else {
+        PREFIX_CHAR_LEN
+    };
+    let time = len + 1..len + 1 + TIME_CHAR_LEN;
+
+    let mut canonical = [0; STR_LEN];
+    canonical[..len].copy_from_slice(&bytes[TIME_CHAR_LEN..TIME_CHAR_LEN + len]);
+    canonical[len] = b'_';
+    canonical[time.clone()].copy_from_slice(&bytes[..TIME_CHAR_LEN]);
+    canonical[time.end..].copy_from_slice(&bytes[TIME_CHAR_LEN + len..]);
+    decode(core::str::from_utf8(&canonical).expect("base32 alphabet is always ASCII"))
 }
 
 /// Encodes the prefix portion of binary data to the prefix and version Strings
+pub fn encode_prefix(binary: &[u8]) -> (String, String) {
+    let (buffer_prefix, buffer_version) = encode_prefix_chars(binary);
+    let prefix = String::from_utf8(buffer_prefix.to_vec())
+        .expect("unexpected failure in base32 encode for upid");
+    let version = String::from_utf8(buffer_version.to_vec())
+        .expect("unexpected failure in base32 encode for upid");
+    (prefix, version)
+}
+
+/// Encodes the prefix portion of binary data to the prefix and version characters
 ///
 /// This process goes from 24 bits `[u8; 3]` to 25 bits (5 base32 characters)
 /// so a 0 bit is implicitly padded to the lsb
-pub fn encode_prefix(binary: &[u8]) -> (String, String) {
+fn encode_prefix_chars(binary: &[u8]) -> ([u8; PREFIX_CHAR_LEN], [u8; VERSION_CHAR_LEN]) {
     let buffer_prefix: [u8; PREFIX_CHAR_LEN] = [
         ENCODE[((binary[0] & 248) >> 3) as usize],
         ENCODE[(((binary[0] & 7) << 2) | ((binary[1] & 192) >> 6)) as usize],
@@ -65,18 +156,14 @@ pub fn encode_prefix(binary: &[u8]) -> (String, String) {
     let buffer_version: [u8; VERSION_CHAR_LEN] = [
         ENCODE[(binary[2] & 15) as usize], // implicitly "add" a 0 bit
     ];
-    let prefix = String::from_utf8(buffer_prefix.to_vec())
-        .expect("unexpected failure in base32 encode for upid");
-    let version = String::from_utf8(buffer_version.to_vec())
-        .expect("unexpected failure in base32 encode for upid");
-    (prefix, version)
+    (buffer_prefix, buffer_version)
 }
 
-/// Encodes the time portion of binary data to a base32 String
+/// Encodes the time portion of binary data to base32 characters
 ///
 /// Unlike the prefix, this has 1:1 bit mapping with 40 bits
-fn encode_time(binary: &[u8]) -> String {
-    let buffer: [u8; TIME_CHAR_LEN] = [
+fn encode_time(binary: &[u8]) -> [u8; TIME_CHAR_LEN] {
+    [
         ENCODE[((binary[0] & 248) >> 3) as usize],
         ENCODE[(((binary[0] & 7) << 2) | ((binary[1] & 192) >> 6)) as usize],
         ENCODE[((binary[1] & 62) >> 1) as usize],
@@ -85,16 +172,15 @@ fn encode_time(binary: &[u8]) -> String {
         ENCODE[((binary[3] & 124) >> 2) as usize],
         ENCODE[(((binary[3] & 3) << 3) | ((binary[4] & 224) >> 5)) as usize],
         ENCODE[(binary[4] & 31) as usize],
-    ];
-    String::from_utf8(buffer.to_vec()).expect("unexpected failure in base32 encode for upid")
+    ]
 }
 
-/// Encodes the randomness portion of binary data to a base32 String
+/// Encodes the randomness portion of binary data to base32 characters
 ///
 /// This process goes from 64 bits `[u8; 8]` to 65 bits (13 base32 characters)
 /// so a 0 bit is implicitly padded to the lsb
-fn encode_rando(binary: &[u8]) -> String {
-    let buffer: [u8; RANDO_CHAR_LEN] = [
+fn encode_rando(binary: &[u8]) -> [u8; RANDO_CHAR_LEN] {
+    [
         ENCODE[((binary[0] & 248) >> 3) as usize],
         ENCODE[(((binary[0] & 7) << 2) | ((binary[1] & 192) >> 6)) as usize],
         ENCODE[((binary[1] & 62) >> 1) as usize],
@@ -108,12 +194,130 @@ fn encode_rando(binary: &[u8]) -> String {
         ENCODE[((binary[6] & 62) >> 1) as usize],
         ENCODE[(((binary[6] & 1) << 4) | ((binary[7] & 240) >> 4)) as usize],
         ENCODE[(binary[7] & 15) as usize], // implicitly "add" a 0 bit
-    ];
-    String::from_utf8(buffer.to_vec()).expect("unexpected failure in base32 encode for upid")
+    ]
+}
+
+/// Whether the binary uses the version 2 variable-length prefix layout
+pub fn is_v2(binary: u128) -> bool {
+    (binary & 15) as u8 == VERSION_V2
+}
+
+/// Position of the lowest random bit, just above the prefix bits of either layout
+pub fn random_shift(binary: u128) -> usize {
+    if is_v2(binary) {
+        PREFIX_SHIFT_V2 + 5 * prefix_len_v2(binary)
+    } else {
+        TIME_SHIFT - 64
+    }
+}
+
+/// Number of prefix characters in a version 2 binary
+fn prefix_len_v2(binary: u128) -> usize {
+    ((binary >> LENGTH_SHIFT_V2) & 7) as usize
+}
+
+/// Encodes a prefix into the low bits of a version 2 binary
+///
+/// Characters outside the alphabet are replaced with 'z' and
+/// anything past [`MAX_PREFIX_LEN_V2`] is clipped.
+/// Returns the encoded bits and the number of random bits left above them.
+pub fn encode_prefix_bits_v2(prefix: &str) -> (u128, usize) {
+    let mut bits: u128 = 0;
+    let mut len: usize = 0;
+    for c in prefix.chars().take(MAX_PREFIX_LEN_V2) {
+        let value = u8::try_from(c).map_or(255, |b| DECODE[b as usize]);
+        bits = (bits << 5) | value.min(31) as u128;
+        len += 1;
+    }
+    let bits = (bits << PREFIX_SHIFT_V2) | ((len as u128) << LENGTH_SHIFT_V2) | VERSION_V2 as u128;
+    (bits, TIME_SHIFT - PREFIX_SHIFT_V2 - 5 * len)
+}
+
+/// Gets the prefix String of a version 2 binary
+pub fn encode_prefix_v2(binary: u128) -> String {
+    let (len, values) = values_v2(binary);
+    values[..len]
+        .iter()
+        .map(|&v| ENCODE[v as usize] as char)
+        .collect()
+}
+
+/// Splits a version 2 binary into its base32 alphabet indices, in string order
+///
+/// Returns the prefix length along with the values.
+/// The random bits are followed by the prefix length and a 0 bit,
+/// so the length can always be found in the second-last character.
+fn values_v2(binary: u128) -> (usize, [u8; CHAR_LEN]) {
+    let len = prefix_len_v2(binary);
+    let rando_shift = PREFIX_SHIFT_V2 + 5 * len;
+    let rando = (binary & ((1 << TIME_SHIFT) - 1)) >> rando_shift;
+
+    let mut values = [0; CHAR_LEN];
+    let (prefix, rest) = values.split_at_mut(len);
+    let (time, rest) = rest.split_at_mut(TIME_CHAR_LEN);
+    let (rando_chars, version) = rest.split_at_mut(rest.len() - VERSION_CHAR_LEN);
+    spread(binary >> PREFIX_SHIFT_V2, prefix);
+    spread(binary >> TIME_SHIFT, time);
+    spread((rando << 4) | ((len as u128) << 1), rando_chars);
+    version[0] = (binary & 15) as u8;
+    (len, values)
+}
+
+/// Encodes a version 2 binary into a buffer of base32 characters
+fn encode_v2_into(binary: u128, buffer: &mut [u8; STR_LEN]) {
+    let (len, values) = values_v2(binary);
+    let (prefix, rest) = values.split_at(len);
+    for (out, &v) in buffer.iter_mut().zip(prefix) {
+        *out = ENCODE[v as usize];
+    }
+    buffer[len] = b'_';
+    for (out, &v) in buffer[len + 1..].iter_mut().zip(rest) {
+        *out = ENCODE[v as usize];
+    }
+}
+
+/// Decodes version 2 characters (already validated and without `_`) into binary
+fn decode_v2(encoded: &[u8]) -> Result<u128, DecodeError> {
+    let mut values = [0; CHAR_LEN];
+    for (v, &b) in values.iter_mut().zip(encoded) {
+        *v = DECODE[b as usize];
+    }
+
+    let length = values[CHAR_LEN - VERSION_CHAR_LEN - 1];
+    if length & 1 != 0 {
+        return Err(DecodeError::Overflow);
+    }
+    let len = ((length >> 1) & 7) as usize;
+
+    let prefix = gather(&values[..len]);
+    let time = gather(&values[len..len + TIME_CHAR_LEN]);
+    let rando = gather(&values[len + TIME_CHAR_LEN..CHAR_LEN - VERSION_CHAR_LEN]) >> 4;
+
+    Ok((time << TIME_SHIFT)
+        | (rando << (PREFIX_SHIFT_V2 + 5 * len))
+        | (prefix << PREFIX_SHIFT_V2)
+        | ((len as u128) << LENGTH_SHIFT_V2)
+        | VERSION_V2 as u128)
+}
+
+/// Writes the low bits of `value` into `out` as 5-bit chunks, msb first
+pub fn spread(value: u128, out: &mut [u8]) {
+    let n = out.len();
+    for (i, v) in out.iter_mut().enumerate() {
+        *v = ((value >> (5 * (n - 1 - i))) & 31) as u8;
+    }
+}
+
+/// Joins 5-bit chunks, msb first, into the low bits of a u128
+pub fn gather(values: &[u8]) -> u128 {
+    values.iter().fold(0, |acc, &v| (acc << 5) | v as u128)
 }
 
 /// An error that can occur when decoding a base32 string
+///
+/// More variants may be added, so matches need a wildcard arm.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[non_exhaustive]
 pub enum DecodeError {
     /// The length of the string does not match the expected length
     InvalidLength,
@@ -121,6 +325,33 @@ pub enum DecodeError {
     InvalidChar,
     /// Text representation overflows random or prefix chunks
     Overflow,
+    /// The trailing version character is not one this crate knows how to decode
+    UnsupportedVersion(char),
+    /// The Upid is valid but doesn't have the prefix that was asked for
+    UnexpectedPrefix,
+}
+
+impl DecodeError {
+    /// A stable snake_case code for the error, for machine-readable responses
+    ///
+    /// Unlike the `Display` message, this won't change between releases.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let err = Upid::from_string("user_short").unwrap_err();
+    /// assert_eq!(err.code(), "invalid_length");
+    /// ```
+    pub const fn code(&self) -> &'static str {
+        match self {
+            DecodeError::InvalidLength => "invalid_length",
+            DecodeError::InvalidChar => "invalid_char",
+            DecodeError::Overflow => "overflow",
+            DecodeError::UnsupportedVersion(_) => "unsupported_version",
+            DecodeError::UnexpectedPrefix => "unexpected_prefix",
+        }
+    }
 }
 
 impl std::error::Error for DecodeError {}
@@ -131,30 +362,156 @@ impl fmt::Display for DecodeError {
             DecodeError::InvalidLength => "invalid length",
             DecodeError::InvalidChar => "invalid character",
             DecodeError::Overflow => "overflow",
+            DecodeError::UnexpectedPrefix => "unexpected prefix",
+            DecodeError::UnsupportedVersion(version) => {
+                return write!(f, "unsupported version '{}'", version);
+            }
         };
         write!(f, "{}", text)
     }
 }
 
+/// Decodes the 26 validated characters of one version, with any `_` removed
+type Decoder = fn(&[u8]) -> Result<u128, DecodeError>;
+
+/// Decoders for each supported version, keyed by the trailing version character
+const DECODERS: &[(u8, Decoder)] = &[(b'a', decode_v1), (b'b', decode_v2), (b'c', decode_v1)];
+
 /// Decodes the encoded string to u128 binary
 ///
 /// Decoding is fallible and will return a `DecodeError` if the string
-/// is too long or includes characters outside the alphabet. This means
+/// is too long, includes characters outside the alphabet, or ends in
+/// a version character that isn't supported. This means
 /// all upstream functions also need to return `Result`.
 ///
 /// A future API might add an infallible version.
 pub fn decode(encoded: &str) -> Result<u128, DecodeError> {
-    let encoded = encoded.replace('_', "");
-    if encoded.len() != CHAR_LEN {
+    let bytes = strip(encoded)?;
+    let version = bytes[CHAR_LEN - 1];
+    match DECODERS.iter().find(|(v, _)| *v == version) {
+        Some((_, decoder)) => decoder(&bytes),
+        None => Err(DecodeError::UnsupportedVersion(version as char)),
+    }
+}
+
+/// Decodes anything written by [`encode_into`], whatever its version
+///
+/// Unlike [`decode`], unsupported versions aren't an error: they are read
+/// with the version 1 layout, which is how [`encode_into`] writes them.
+#[cfg(feature = "encoded")]
+pub fn decode_encoded(encoded: &str) -> Result<u128, DecodeError> {
+    let bytes = strip(encoded)?;
+    match bytes[CHAR_LEN - 1] {
+        b'b' => decode_v2(&bytes),
+        _ => decode_v1(&bytes),
+    }
+}
+
+/// Checks that the string would decode, without building the binary
+///
+/// This returns the same error as [`decode`] for any input.
+pub fn validate(encoded: &str) -> Result<(), DecodeError> {
+    let bytes = strip(encoded)?;
+    // the second-last character holds the spare bits of either version
+    let spare = DECODE[bytes[CHAR_LEN - 2] as usize];
+    match bytes[CHAR_LEN - 1] {
+        b'a' | b'c' if spare > 15 => Err(DecodeError::Overflow),
+        b'b' if spare & 1 != 0 => Err(DecodeError::Overflow),
+        b'a' | b'b' | b'c' => Ok(()),
+        version => Err(DecodeError::UnsupportedVersion(version as char)),
+    }
+}
+
+/// Checks whether the string is a valid Upid, see [`validate`]
+pub fn is_valid(encoded: &str) -> bool {
+    validate(encoded).is_ok()
+}
+
+/// Copies the characters into a stack buffer, skipping `_`, and checks the length and alphabet
+fn strip(encoded: &str) -> Result<[u8; CHAR_LEN], DecodeError> {
+    let mut bytes = [0u8; CHAR_LEN];
+    let mut len = 0;
+    for &byte in encoded.as_bytes() {
+        if byte == b'_' {
+            continue;
+        }
+        if len == CHAR_LEN {
+            return Err(DecodeError::InvalidLength);
+        }
+        bytes[len] = byte;
+        len += 1;
+    }
+    if len != CHAR_LEN {
         return Err(DecodeError::InvalidLength);
     }
 
-    if encoded.bytes().any(|b| !ENCODE.contains(&b)) {
+    if bytes.iter().any(|&b| DECODE[b as usize] == 255) {
         return Err(DecodeError::InvalidChar);
     }
-    let bytes: &[u8] = encoded.as_bytes();
+    Ok(bytes)
+}
+
+/// Decodes any string to u128 binary, without failing
+///
+/// Underscores are ignored and ASCII upper-case is folded to lower-case.
+/// Other characters outside the alphabet become 'z', missing characters become '2' (zero)
+/// and anything past 26 characters is dropped. Overflow bits are cleared and versions
+/// other than `b` and `c` are replaced with `a`, so the result is always a valid Upid.
+pub fn decode_lossy(encoded: &str) -> u128 {
+    let mut chars = encoded.chars().filter(|&c| c != '_');
+    let mut values = [0u8; CHAR_LEN];
+    for value in values.iter_mut() {
+        *value = match chars.next() {
+            Some(c) => {
+                u8::try_from(c.to_ascii_lowercase()).map_or(31, |b| DECODE[b as usize].min(31))
+            }
+            None => 0,
+        };
+    }
+
+    // clear the bits that would overflow, and fall back to version 'a' for unknown versions
+    let decoder: Decoder = if values[CHAR_LEN - 1] == VERSION_V2 {
+        let len = ((values[CHAR_LEN - 2] >> 1) & 7).min(MAX_PREFIX_LEN_V2 as u8);
+        values[CHAR_LEN - 2] = (values[CHAR_LEN - 2] & 16) | (len << 1);
+        decode_v2
+    } else {
+        values[CHAR_LEN - 2] &= 15;
+        if values[CHAR_LEN - 1] != VERSION_V3 {
+            values[CHAR_LEN - 1] = VERSION_V1;
+        }
+        decode_v1
+    };
+    decoder(&values.map(|v| ENCODE[v as usize])).expect("overflow bits were cleared")
+}
 
-    let prefix_bytes: Vec<u8> = [&bytes[0..PREFIX_CHAR_LEN], &[bytes[bytes.len() - 1]]].concat();
+/// Decodes a string to u128 binary, skipping all validation
+///
+/// # Safety
+/// `encoded` must be a string that [`decode`] accepts, eg one that was already validated.
+/// Anything else is undefined behaviour.
+pub unsafe fn decode_unchecked(encoded: &str) -> u128 {
+    let mut bytes = [0u8; CHAR_LEN];
+    let mut i = 0;
+    for &b in encoded.as_bytes() {
+        if b != b'_' {
+            *bytes.get_unchecked_mut(i) = b;
+            i += 1;
+        }
+    }
+
+    let decoder: Decoder = if bytes[CHAR_LEN - 1] == ENCODE[VERSION_V2 as usize] {
+        decode_v2
+    } else {
+        decode_v1
+    };
+    decoder(&bytes).unwrap_unchecked()
+}
+
+/// Decodes version 1 characters (already validated and without `_`) into binary
+fn decode_v1(bytes: &[u8]) -> Result<u128, DecodeError> {
+    let mut prefix_bytes = [0u8; PREFIX_CHAR_LEN + 1];
+    prefix_bytes[..PREFIX_CHAR_LEN].copy_from_slice(&bytes[..PREFIX_CHAR_LEN]);
+    prefix_bytes[PREFIX_CHAR_LEN] = bytes[bytes.len() - 1];
 
     let prefix = decode_prefix(&prefix_bytes)?;
     let time = decode_time(&bytes[PREFIX_CHAR_LEN..END_TIME_CHAR])?;
@@ -263,7 +620,7 @@ mod tests {
             + ((array[1] as u128) << (64 + 24 + 24))
             + ((array[2] as u128) << (64 + 24 + 16))
             + ((array[3] as u128) << (64 + 24 + 8))
-            + ((array[4] as u128) << (64 + 24 + 0))
+            + ((array[4] as u128) << (64 + 24))
     }
 
     #[test]
@@ -271,19 +628,100 @@ mod tests {
         let timestamp: u128 = 1720560233826;
         let time_bits = timestamp >> 1;
         let random: u64 = 1218987987987368123;
-        let upid = (time_bits << 88) | ((random as u128) << 24) | (5 << 16) | (5 << 8) | 5;
+        let upid = (time_bits << 88) | ((random as u128) << 24) | (5 << 16) | (5 << 8) | 6;
         let text = encode(upid);
         let end = decode(&text).unwrap();
         assert!(end == upid);
     }
 
+    #[test]
+    fn test_decode_invalid() {
+        let text = "user_2acdrlkjmhs6ar53taem6a";
+        assert_eq!(decode("user2acdrlkjmhs6ar53taem6a"), decode(text));
+        assert_eq!(decode("_user__2acdrlkjmhs6ar53taem6a_"), decode(text));
+        assert_eq!(
+            decode("user_2acdrlkjmhs6ar53taem6aa"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decode("user_2acdrlkjmhs6ar53taem6"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            decode("user_2acdrlkjmhs6ar53taem6A"),
+            Err(DecodeError::InvalidChar)
+        );
+        assert_eq!(
+            decode("user_2acdrlkjmhs6ar53taemé"),
+            Err(DecodeError::InvalidChar)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        for text in [
+            "user_2acdrlkjmhs6ar53taem6a",
+            "user_2acdrlkjmhs6ar53taemza",
+            "user_2acdrlkjmhs6ar53taem6z",
+            "user_2acdrlkjmhs6ar53taem6",
+            "user_2acdrlkjmhs6ar53taem6A",
+            "ab_2acdrlkjmhs6ar53taemmcqb",
+            "ab_2acdrlkjmhs6ar53taemmcrb",
+        ] {
+            assert_eq!(validate(text), decode(text).map(|_| ()), "{text}");
+            assert_eq!(is_valid(text), decode(text).is_ok());
+        }
+        assert!(is_valid("ab_2acdrlkjmhs6ar53taemmcqb"));
+        assert_eq!(
+            validate("ab_2acdrlkjmhs6ar53taemmcrb"),
+            Err(DecodeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(DecodeError::InvalidLength.code(), "invalid_length");
+        assert_eq!(DecodeError::InvalidChar.code(), "invalid_char");
+        assert_eq!(DecodeError::Overflow.code(), "overflow");
+        assert_eq!(
+            DecodeError::UnsupportedVersion('z').code(),
+            "unsupported_version"
+        );
+        assert_eq!(DecodeError::UnexpectedPrefix.code(), "unexpected_prefix");
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let upid = decode("user_aaccvpp5guht4dts56je5a").unwrap();
+        let text = encode(upid & !15);
+        assert_eq!(&text[text.len() - 1..], "2");
+        assert_eq!(decode(&text), Err(DecodeError::UnsupportedVersion('2')));
+        assert_eq!(
+            decode("user_aaccvpp5guht4dts56je5z"),
+            Err(DecodeError::UnsupportedVersion('z'))
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_v2() {
+        let timestamp: u128 = 1720560233826;
+        let bits: u128 = 0x005f_3c9a_217e_0b44_d1a7;
+        for len in 0..8 {
+            let upid = ((timestamp >> 8) << 88) | (bits << 7) | (len << 4) | VERSION_V2 as u128;
+            let text = encode(upid);
+            assert_eq!(text.len(), STR_LEN);
+            assert_eq!(text.find('_'), Some(len as usize));
+            assert_eq!(decode(&text), Ok(upid), "{text}");
+        }
+    }
+
     #[test]
     fn test_encode_decode_time() {
         let timestamp: u128 = 1720560233826;
         let time_bits = timestamp >> 1;
         let t_in = (time_bits << 88).to_be_bytes();
         let enc = encode_time(&t_in);
-        let tout = decode_time(&enc.as_bytes()).unwrap();
+        let tout = decode_time(&enc).unwrap();
         let final_t = (time_as128(&tout) >> 88) << 1;
         assert!(timestamp - final_t < EPS);
     }
@@ -0,0 +1,117 @@
+//! The low-level base32 encoding used by Upids, for reuse by other implementations.
+//!
+//! Everything here works on the raw `u128`, and follows the spec in the README:
+//!
+//! - The alphabet is [`ALPHABET`], ordered so that digits sort before letters.
+//! - Version `a` is binary `time (40) | random (64) | prefix (20) | version (4)`, and the string
+//!   is the 4 prefix characters, `_`, then 8 time, 13 random and 1 version character.
+//!   The random and version characters only carry 4 bits each, so anything past `j`
+//!   in those positions is an overflow.
+//! - Version `b` is binary `time (40) | random (81 - 5L) | prefix (5L) | L (3) | version (4)`,
+//!   for a prefix of `L` characters from 0 to 5, and the string is the prefix, `_`, 8 time
+//!   characters, then `17 - L` characters holding the random bits, `L` and a zero bit,
+//!   and finally the version character.
+//! - Version `c` is the same as version `a`, but the time counts from
+//!   [`CUSTOM_EPOCH_MILLISECONDS`](crate::CUSTOM_EPOCH_MILLISECONDS) rather than the Unix epoch.
+//!
+//! Only the functions and constants in this module are covered by semver;
+//! the rest of the implementation can change between releases.
+//!
+//! ```rust
+//! use upid::base32;
+//!
+//! let binary = base32::decode("user_2acdrlkjmhs6ar53taem6a").unwrap();
+//! assert_eq!(base32::encode(binary), "user_2acdrlkjmhs6ar53taem6a");
+//! assert_eq!(base32::encode_prefix(binary), "user");
+//! ```
+
+use crate::{b32, DecodeError, Upid};
+
+/// The 32-character alphabet, indexed by 5-bit value
+pub const ALPHABET: &[u8; 32] = b32::ENCODE;
+
+/// Length of an encoded Upid, including the `_`
+pub const STR_LEN: usize = b32::STR_LEN;
+
+/// Length of a version `a` prefix
+pub const PREFIX_LEN: usize = 4;
+
+/// Encodes a binary Upid as a string
+pub fn encode(binary: u128) -> String {
+    b32::encode(binary)
+}
+
+/// Encodes a binary Upid into a buffer, without allocating
+pub fn encode_into(binary: u128, buffer: &mut [u8; STR_LEN]) {
+    b32::encode_into(binary, buffer)
+}
+
+/// Decodes a string into a binary Upid, checking its length, alphabet and version
+pub fn decode(encoded: &str) -> Result<u128, DecodeError> {
+    b32::decode(encoded)
+}
+
+/// Checks whether a string would [`decode`], without building the binary
+pub fn is_valid(encoded: &str) -> bool {
+    b32::is_valid(encoded)
+}
+
+/// Gets the prefix characters of a binary Upid, of either version
+pub fn encode_prefix(binary: u128) -> String {
+    Upid(binary).prefix()
+}
+
+/// Decodes a version `a` prefix into its 24 low bits, including the version
+///
+/// Unlike the constructors, which pad or clip the prefix and replace characters
+/// outside the alphabet, this only accepts exactly four characters from the alphabet.
+///
+/// ```rust
+/// use upid::base32;
+///
+/// assert_eq!(base32::decode_prefix("user"), Ok(0xd61576));
+/// ```
+pub fn decode_prefix(prefix: &str) -> Result<u128, DecodeError> {
+    if prefix.len() != PREFIX_LEN {
+        return Err(DecodeError::InvalidLength);
+    }
+    if !prefix.bytes().all(|b| ALPHABET.contains(&b)) {
+        return Err(DecodeError::InvalidChar);
+    }
+    let with_version = [prefix.as_bytes(), &[crate::VERSION as u8]].concat();
+    let bytes = b32::decode_prefix(&with_version)?;
+    Ok(u128::from(bytes[0]) << 16 | u128::from(bytes[1]) << 8 | u128::from(bytes[2]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn base32_cycle() {
+        let binary = decode(TEXT).unwrap();
+        assert_eq!(binary, 0x01909bc60f9370435c61c99524d61576);
+        assert_eq!(encode(binary), TEXT);
+
+        let mut buffer = [0; STR_LEN];
+        encode_into(binary, &mut buffer);
+        assert_eq!(&buffer, TEXT.as_bytes());
+
+        let v2 = Upid::new_v2("tx");
+        assert_eq!(decode(&encode(v2.0)), Ok(v2.0));
+        assert_eq!(encode_prefix(v2.0), "tx");
+    }
+
+    #[test]
+    fn base32_prefix() {
+        let binary = decode(TEXT).unwrap();
+        assert_eq!(encode_prefix(binary), "user");
+        assert_eq!(decode_prefix("user"), Ok(binary & 0xff_ffff));
+
+        assert_eq!(decode_prefix("use"), Err(DecodeError::InvalidLength));
+        assert_eq!(decode_prefix("USER"), Err(DecodeError::InvalidChar));
+        assert_eq!(decode_prefix("us1r"), Err(DecodeError::InvalidChar));
+    }
+}
@@ -0,0 +1,44 @@
+//! Serialize Upid with borsh.
+//!
+//! A Upid is written as its 16 big-endian bytes (the same as [`Upid::to_bytes`]),
+//! rather than borsh's little-endian `u128`, so the encoded form sorts by time.
+
+use std::io::{Read, Result, Write};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Upid;
+
+impl BorshSerialize for Upid {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+}
+
+impl BorshDeserialize for Upid {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut bytes = [0u8; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(Upid::from_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn borsh_cycle() {
+        let want = Upid::new("user");
+        let bytes = borsh::to_vec(&want).unwrap();
+        let got: Upid = borsh::from_slice(&bytes).unwrap();
+
+        assert_eq!(bytes, want.to_bytes());
+        assert_eq!(got, want)
+    }
+
+    #[test]
+    fn borsh_too_short() {
+        assert!(borsh::from_slice::<Upid>(&[0; 15]).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Store Upids in MongoDB as BSON binary.
+//!
+//! A Upid is stored as a 16-byte binary with the generic subtype by default,
+//! or the UUID subtype with [`to_uuid_binary`]. The time is in the leading bytes,
+//! so binaries sort by time and can be range-queried with [`time_bound`].
+//!
+//! ```rust
+//! use bson::doc;
+//! use serde::{Deserialize, Serialize};
+//! use std::time::{Duration, SystemTime};
+//! use upid::Upid;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     #[serde(rename = "_id", with = "upid::bson::as_binary")]
+//!     id: Upid,
+//! }
+//!
+//! let since = SystemTime::now() - Duration::from_secs(3600);
+//! let filter = doc! { "_id": { "$gte": upid::bson::time_bound(since) } };
+//! ```
+
+use std::fmt;
+use std::time::SystemTime;
+
+use bson::spec::BinarySubtype;
+use bson::{Binary, Bson};
+
+use crate::{from_parts, milliseconds_since_epoch, DecodeError, Upid};
+
+/// Error when a BSON value can't be converted to a Upid
+#[derive(Debug, Clone, PartialEq)]
+pub enum BsonError {
+    /// The value isn't a generic or UUID binary, or a string
+    UnexpectedType,
+    /// The binary or string isn't a valid Upid
+    Decode(DecodeError),
+}
+
+impl fmt::Display for BsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BsonError::UnexpectedType => write!(f, "expected a binary or string upid"),
+            BsonError::Decode(err) => write!(f, "invalid upid: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BsonError {}
+
+impl From<Upid> for Binary {
+    fn from(upid: Upid) -> Binary {
+        Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: upid.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<Upid> for Bson {
+    fn from(upid: Upid) -> Bson {
+        Bson::Binary(upid.into())
+    }
+}
+
+/// Converts a Upid to a binary with the UUID subtype, for collections that already use UUIDs
+pub fn to_uuid_binary(upid: Upid) -> Binary {
+    Binary {
+        subtype: BinarySubtype::Uuid,
+        bytes: upid.to_bytes().to_vec(),
+    }
+}
+
+/// The smallest Upid binary at `datetime`, to use as a bound in range queries
+///
+/// Every version `a` or `b` Upid created at or after `datetime` (of any prefix) is greater
+/// than or equal to this. Version `c` counts its time from a later epoch, so this doesn't
+/// bound it.
+pub fn time_bound(datetime: SystemTime) -> Binary {
+    let milliseconds = milliseconds_since_epoch(datetime);
+    from_parts(milliseconds >> 8, 0, 0).into()
+}
+
+impl TryFrom<Binary> for Upid {
+    type Error = BsonError;
+
+    fn try_from(binary: Binary) -> Result<Upid, BsonError> {
+        match binary.subtype {
+            BinarySubtype::Generic | BinarySubtype::Uuid => {
+                Upid::from_slice(&binary.bytes).map_err(BsonError::Decode)
+            }
+            _ => Err(BsonError::UnexpectedType),
+        }
+    }
+}
+
+impl TryFrom<Bson> for Upid {
+    type Error = BsonError;
+
+    fn try_from(value: Bson) -> Result<Upid, BsonError> {
+        match value {
+            Bson::Binary(binary) => binary.try_into(),
+            Bson::String(text) => Upid::from_string(&text).map_err(BsonError::Decode),
+            _ => Err(BsonError::UnexpectedType),
+        }
+    }
+}
+
+/// Serde helpers to store a Upid field as a generic BSON binary
+///
+/// Use with `#[serde(with = "upid::bson::as_binary")]`.
+pub mod as_binary {
+    use bson::Binary;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::Upid;
+
+    /// Serializes a Upid as a generic BSON binary
+    pub fn serialize<S: Serializer>(upid: &Upid, serializer: S) -> Result<S::Ok, S::Error> {
+        Binary::from(*upid).serialize(serializer)
+    }
+
+    /// Deserializes a Upid from a generic or UUID BSON binary
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Upid, D::Error> {
+        let binary = Binary::deserialize(deserializer)?;
+        Upid::try_from(binary).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        #[serde(rename = "_id", with = "as_binary")]
+        id: Upid,
+    }
+
+    #[test]
+    fn binary_cycle() {
+        let want = Upid::new("user");
+
+        assert_eq!(Upid::try_from(Binary::from(want)), Ok(want));
+        assert_eq!(Upid::try_from(to_uuid_binary(want)), Ok(want));
+        assert_eq!(Upid::try_from(Bson::from(want)), Ok(want));
+        assert_eq!(Upid::try_from(Bson::String(want.to_string())), Ok(want));
+    }
+
+    #[test]
+    fn invalid() {
+        let short = Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: vec![0; 15],
+        };
+        let md5 = Binary {
+            subtype: BinarySubtype::Md5,
+            bytes: vec![0; 16],
+        };
+
+        assert_eq!(
+            Upid::try_from(short),
+            Err(BsonError::Decode(DecodeError::InvalidLength))
+        );
+        assert_eq!(Upid::try_from(md5), Err(BsonError::UnexpectedType));
+        assert_eq!(
+            Upid::try_from(Bson::Int32(1)),
+            Err(BsonError::UnexpectedType)
+        );
+    }
+
+    #[test]
+    fn serde_cycle() {
+        let user = User {
+            id: Upid::new("user"),
+        };
+        let doc = bson::to_document(&user).unwrap();
+
+        assert_eq!(doc.get("_id"), Some(&Bson::from(user.id)));
+        assert_eq!(bson::from_document::<User>(doc).unwrap(), user);
+    }
+
+    #[test]
+    fn time_bound_sorts() {
+        let now = SystemTime::now();
+        let bound = Upid::try_from(time_bound(now)).unwrap();
+
+        assert!(bound <= Upid::from_prefix_and_datetime("aaaa", now));
+        assert!(bound > Upid::from_prefix_and_datetime("zzzz", now - Duration::from_secs(1)));
+
+        // version 'c' binaries sort by their own epoch
+        let ms = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let v3 = Upid::from_prefix_and_milliseconds_v3("aaaa", ms);
+        assert!(bound > v3);
+    }
+}
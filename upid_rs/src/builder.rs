@@ -0,0 +1,276 @@
+//! A single entry point for building Upids from any mix of parts.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use crate::{
+    b32, from_parts, milliseconds_since_epoch, prefix_bits, rng, Clock, SystemClock, Upid,
+    CUSTOM_EPOCH_MILLISECONDS, MAX_MILLISECONDS, VERSION,
+};
+
+/// The reasons [`UpidBuilder::try_build`] can fail
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The time can't be represented by the chosen version
+    TimestampOutOfRange,
+    /// The version isn't one of [`SUPPORTED_VERSIONS`](crate::SUPPORTED_VERSIONS)
+    UnsupportedVersion(char),
+}
+
+impl std::error::Error for BuildError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            BuildError::TimestampOutOfRange => f.write_str("timestamp out of range"),
+            BuildError::UnsupportedVersion(c) => write!(f, "unsupported version '{c}'"),
+        }
+    }
+}
+
+/// Builds a Upid, with the current time and random bits unless told otherwise
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use upid::UpidBuilder;
+///
+/// let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1720600366848);
+/// let upid = UpidBuilder::new()
+///     .prefix("user")
+///     .time(time)
+///     .random(0x9370435c61c99524)
+///     .build();
+///
+/// assert_eq!(upid.to_string(), "user_2acdrlkjmhs6ar53taem6a");
+///
+/// let order = UpidBuilder::new().prefix("order").version('b').build();
+/// assert_eq!(order.prefix(), "order");
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpidBuilder {
+    prefix: String,
+    version: char,
+    milliseconds: Option<u128>,
+    random: Option<u128>,
+}
+
+impl Default for UpidBuilder {
+    fn default() -> UpidBuilder {
+        UpidBuilder {
+            prefix: String::new(),
+            version: VERSION,
+            milliseconds: None,
+            random: None,
+        }
+    }
+}
+
+impl UpidBuilder {
+    /// Creates a builder for a Upid with an empty prefix, the current time and random bits
+    pub fn new() -> UpidBuilder {
+        UpidBuilder::default()
+    }
+
+    /// Sets the prefix, handled the same way as in [`Upid::from_prefix`],
+    /// or [`Upid::new_v2`] for version `b`
+    pub fn prefix(mut self, prefix: &str) -> UpidBuilder {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Sets the version, one of [`SUPPORTED_VERSIONS`](crate::SUPPORTED_VERSIONS), instead of [`VERSION`]
+    pub fn version(mut self, version: char) -> UpidBuilder {
+        self.version = version;
+        self
+    }
+
+    /// Sets the time, clamped to the Unix epoch as in [`Upid::from_prefix_and_datetime`]
+    pub fn time(mut self, time: SystemTime) -> UpidBuilder {
+        self.milliseconds = Some(milliseconds_since_epoch(time));
+        self
+    }
+
+    /// Sets the time in milliseconds since the Unix epoch
+    pub fn milliseconds(mut self, milliseconds: u128) -> UpidBuilder {
+        self.milliseconds = Some(milliseconds);
+        self
+    }
+
+    /// Sets the random bits, instead of drawing them from the RNG
+    ///
+    /// Version `b` has 56 to 81 random bits, depending on the prefix length.
+    /// These are the lowest 64 of them, and any others are zero.
+    pub fn random(mut self, random: u64) -> UpidBuilder {
+        self.random = Some(random as u128);
+        self
+    }
+
+    /// Sets all the random bits, as returned by [`Upid::random`]
+    ///
+    /// Bits beyond those the version holds are dropped.
+    pub fn random_bits(mut self, random: u128) -> UpidBuilder {
+        self.random = Some(random);
+        self
+    }
+
+    /// Builds the Upid, filling in anything not set
+    ///
+    /// Times out of range wrap around, or are clamped to [`CUSTOM_EPOCH_MILLISECONDS`]
+    /// for version `c`, as in the constructors. Use [`UpidBuilder::try_build`] to catch them.
+    ///
+    /// # Panics
+    /// Panics if the version isn't supported.
+    pub fn build(&self) -> Upid {
+        let milliseconds = self.milliseconds_or_now();
+        let milliseconds = match self.version {
+            'c' => milliseconds.saturating_sub(CUSTOM_EPOCH_MILLISECONDS),
+            _ => milliseconds,
+        };
+        self.build_parts(milliseconds)
+            .unwrap_or_else(|err| panic!("invalid upid builder: {err}"))
+    }
+
+    /// Builds the Upid, or an error if the version isn't supported
+    /// or the time can't be represented by it
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{BuildError, UpidBuilder, MAX_MILLISECONDS};
+    ///
+    /// let builder = UpidBuilder::new().prefix("user");
+    /// assert!(builder.clone().milliseconds(1720600366848).try_build().is_ok());
+    /// assert_eq!(
+    ///     builder.clone().milliseconds(MAX_MILLISECONDS + 1).try_build(),
+    ///     Err(BuildError::TimestampOutOfRange),
+    /// );
+    /// assert_eq!(
+    ///     builder.version('z').try_build(),
+    ///     Err(BuildError::UnsupportedVersion('z')),
+    /// );
+    /// ```
+    pub fn try_build(&self) -> Result<Upid, BuildError> {
+        let milliseconds = self.milliseconds_or_now();
+        let milliseconds = match self.version {
+            'c' => milliseconds
+                .checked_sub(CUSTOM_EPOCH_MILLISECONDS)
+                .ok_or(BuildError::TimestampOutOfRange)?,
+            _ => milliseconds,
+        };
+        if milliseconds > MAX_MILLISECONDS {
+            return Err(BuildError::TimestampOutOfRange);
+        }
+        self.build_parts(milliseconds)
+    }
+
+    fn milliseconds_or_now(&self) -> u128 {
+        self.milliseconds
+            .unwrap_or_else(|| milliseconds_since_epoch(SystemClock.now()))
+    }
+
+    /// Builds from milliseconds already counted from the version's epoch
+    fn build_parts(&self, milliseconds: u128) -> Result<Upid, BuildError> {
+        let prefix_bits = match self.version {
+            'b' => b32::encode_prefix_bits_v2(&self.prefix).0,
+            'c' => (prefix_bits(&self.prefix) & !15) | b32::VERSION_V3 as u128,
+            v if v == VERSION => prefix_bits(&self.prefix),
+            v => return Err(BuildError::UnsupportedVersion(v)),
+        };
+        let random = match self.random {
+            Some(random) => random,
+            // version `b` can have more than 64 random bits
+            None => ((rng::random_u64() as u128) << 64) | rng::random_u64() as u128,
+        };
+        Ok(from_parts(milliseconds >> 8, random, prefix_bits))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        let before = Upid::new("zzzz");
+        let upid = UpidBuilder::new().build();
+
+        assert_eq!(upid.prefix(), "zzzz");
+        assert!(upid.milliseconds() >= before.milliseconds());
+        assert_ne!(UpidBuilder::new().build(), UpidBuilder::new().build());
+    }
+
+    #[test]
+    fn builder_parts() {
+        let builder = UpidBuilder::new()
+            .prefix("user")
+            .milliseconds(1720600366848)
+            .random(0x9370435c61c99524);
+
+        let upid = builder.build();
+        assert_eq!(upid.to_hex(), "01909bc60f9370435c61c99524d61576");
+        assert_eq!(builder.build(), upid);
+
+        let random = UpidBuilder::new()
+            .prefix("user")
+            .milliseconds(1720600366848)
+            .build();
+        assert_eq!(random.milliseconds(), 1720600366848);
+        assert_eq!(random.prefix(), "user");
+    }
+
+    #[test]
+    fn builder_versions() {
+        let builder = UpidBuilder::new()
+            .prefix("tx")
+            .milliseconds(1720600366848)
+            .random(0x9370435c61c99524);
+
+        let b = builder.clone().version('b').build();
+        assert_eq!(b.version(), 'b');
+        assert_eq!(b.prefix(), "tx");
+        assert_eq!(b.milliseconds(), 1720600366848);
+
+        let c = builder.clone().version('c').build();
+        assert_eq!(c.version(), 'c');
+        assert_eq!(c.milliseconds(), 1720600366848);
+        assert_eq!(c.to_unix_epoch(), Some(builder.build()));
+    }
+
+    #[test]
+    fn builder_random_bits() {
+        let tx = Upid::new_v2("tx");
+        let rebuilt = UpidBuilder::new()
+            .prefix("tx")
+            .version('b')
+            .milliseconds(u128::from(tx.milliseconds()))
+            .random_bits(tx.random())
+            .build();
+        assert_eq!(rebuilt, tx);
+
+        let upid = UpidBuilder::new().random_bits(u128::MAX).build();
+        assert_eq!(upid.random(), u128::from(u64::MAX));
+    }
+
+    #[test]
+    fn builder_checked() {
+        let builder = UpidBuilder::new().prefix("user");
+
+        assert_eq!(
+            builder
+                .clone()
+                .milliseconds(MAX_MILLISECONDS + 1)
+                .try_build(),
+            Err(BuildError::TimestampOutOfRange)
+        );
+        assert_eq!(
+            builder.clone().version('c').milliseconds(0).try_build(),
+            Err(BuildError::TimestampOutOfRange)
+        );
+        assert_eq!(
+            builder.clone().version('d').try_build(),
+            Err(BuildError::UnsupportedVersion('d'))
+        );
+        assert!(builder.version('c').try_build().is_ok());
+    }
+}
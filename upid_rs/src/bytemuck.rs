@@ -0,0 +1,43 @@
+//! Reinterpret Upids as plain bytes, for memory-mapped files and shared-memory rings.
+//!
+//! [`Upid`] is `#[repr(transparent)]` over its `u128`, so it is [`Pod`] and a
+//! `&[Upid]` can be cast to and from `&[u8]` without copying.
+//!
+//! The bytes are the native-endian `u128`, unlike [`Upid::to_bytes`] which is big-endian,
+//! so only share them between machines of the same endianness.
+//!
+//! ```rust
+//! use upid::Upid;
+//!
+//! let upids = [Upid::new("user"), Upid::new("post")];
+//! let bytes: &[u8] = bytemuck::cast_slice(&upids);
+//! assert_eq!(bytes.len(), 32);
+//!
+//! let back: &[Upid] = bytemuck::cast_slice(bytes);
+//! assert_eq!(back, upids);
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::Upid;
+
+// SAFETY: Upid is repr(transparent) over a u128, which is Zeroable
+unsafe impl Zeroable for Upid {}
+
+// SAFETY: Upid is repr(transparent) over a u128, which is Pod
+unsafe impl Pod for Upid {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cast_cycle() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let bytes = bytemuck::bytes_of(&upid);
+
+        assert_eq!(bytes, upid.0.to_ne_bytes());
+        assert_eq!(bytemuck::pod_read_unaligned::<Upid>(bytes), upid);
+        assert_eq!(Upid::zeroed(), Upid(0));
+    }
+}
@@ -0,0 +1,100 @@
+//! Sources of the current time for Upid generation.
+//!
+//! [`Upid::new`](crate::Upid::new) always uses the [`SystemClock`], but
+//! [`Upid::from_prefix_and_clock`](crate::Upid::from_prefix_and_clock) accepts
+//! any [`Clock`], so tests can freeze or step time.
+
+#[cfg(feature = "test-util")]
+use std::sync::Mutex;
+#[cfg(feature = "test-util")]
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// A source of the current time
+pub trait Clock {
+    /// Returns the current time
+    fn now(&self) -> SystemTime;
+}
+
+/// The real system clock, using [`SystemTime::now`]
+///
+/// With the `test-util` feature, this is stopped while a
+/// [`testing::freeze`](crate::testing::freeze) guard is held on the current thread.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        #[cfg(feature = "test-util")]
+        if let Some(now) = crate::testing::frozen_now() {
+            return now;
+        }
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to, for use in tests
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use upid::{MockClock, Upid};
+///
+/// let clock = MockClock::new(SystemTime::UNIX_EPOCH + Duration::from_millis(1720568902000));
+/// let first = Upid::from_prefix_and_clock("user", &clock);
+///
+/// clock.advance(Duration::from_secs(1));
+/// let second = Upid::from_prefix_and_clock("user", &clock);
+///
+/// assert_eq!(second.milliseconds() - first.milliseconds(), 1024);
+/// ```
+#[cfg(feature = "test-util")]
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<SystemTime>,
+}
+
+#[cfg(feature = "test-util")]
+impl MockClock {
+    /// Creates a clock frozen at the given time
+    pub fn new(now: SystemTime) -> MockClock {
+        MockClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Sets the clock to the given time
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward by the given duration
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let start = SystemTime::UNIX_EPOCH;
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_millis(300));
+        assert_eq!(clock.now(), start + Duration::from_millis(300));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
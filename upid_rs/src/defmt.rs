@@ -0,0 +1,26 @@
+//! Log Upids from embedded firmware with defmt.
+
+use crate::{b32, Upid};
+
+/// Renders the usual string form, encoded on the stack
+impl defmt::Format for Upid {
+    fn format(&self, f: defmt::Formatter) {
+        let mut buffer = [0; b32::STR_LEN];
+        b32::encode_into(self.0, &mut buffer);
+        let text = core::str::from_utf8(&buffer).unwrap_or("invalid upid");
+        defmt::write!(f, "{=str}", text);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_format<T: defmt::Format>() {}
+
+    // Actually logging needs a global logger and linker script from the target
+    #[test]
+    fn upid_is_format() {
+        assert_format::<Upid>();
+    }
+}
@@ -0,0 +1,68 @@
+//! Upids derived from a name, for idempotent ingestion.
+
+use sha2::{Digest, Sha256};
+
+use crate::{from_parts, prefix_bits, Upid};
+
+impl Upid {
+    /// Creates a Upid from a namespace and a name, in the style of UUIDv5
+    ///
+    /// The same `prefix`, `namespace` and `name` always give the same Upid, so an
+    /// external key can be mapped to a Upid without a lookup table.
+    /// The timestamp and random bits all come from a SHA-256 hash, so these Upids
+    /// carry no creation time and are **not** time-sortable.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let namespace = Upid::from_string("srce_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// let a = Upid::new_deterministic("user", &namespace, b"stripe:cus_123");
+    /// let b = Upid::new_deterministic("user", &namespace, b"stripe:cus_123");
+    ///
+    /// assert_eq!(a, b);
+    /// assert_eq!(a.prefix(), "user");
+    /// ```
+    pub fn new_deterministic(prefix: &str, namespace: &Upid, name: &[u8]) -> Upid {
+        let digest = Sha256::new()
+            .chain_update(namespace.to_bytes())
+            .chain_update(name)
+            .finalize();
+        let mut hash = [0; 16];
+        hash.copy_from_slice(&digest[..16]);
+
+        // keep the top 104 bits of the hash, in place of the timestamp and random bits
+        let hash = u128::from_be_bytes(hash);
+        from_parts(hash >> 88, hash >> 24, prefix_bits(prefix))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deterministic_is_stable() {
+        let namespace = Upid::from_string("srce_2acdrlkjmhs6ar53taem6a").unwrap();
+        let a = Upid::new_deterministic("user", &namespace, b"key");
+
+        assert_eq!(a, Upid::new_deterministic("user", &namespace, b"key"));
+        assert_eq!(a.prefix(), "user");
+        assert_eq!(a.version(), 'a');
+        assert_eq!(Upid::from_string(&a.to_string()), Ok(a));
+    }
+
+    #[test]
+    fn deterministic_depends_on_inputs() {
+        let namespace = Upid::from_string("srce_2acdrlkjmhs6ar53taem6a").unwrap();
+        let other = Upid::from_string("srce_2acdrlkjmhs6ar53taem7a").unwrap();
+        let a = Upid::new_deterministic("user", &namespace, b"key");
+
+        assert_ne!(a, Upid::new_deterministic("user", &namespace, b"kez"));
+        assert_ne!(a, Upid::new_deterministic("user", &other, b"key"));
+
+        let b = Upid::new_deterministic("acct", &namespace, b"key");
+        assert_eq!(u128::from(a) >> 24, u128::from(b) >> 24);
+        assert_eq!(b.prefix(), "acct");
+    }
+}
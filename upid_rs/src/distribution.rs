@@ -0,0 +1,92 @@
+//! Sample Upids from a `rand` RNG, for property tests and simulations.
+
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+
+use crate::{from_parts, milliseconds_since_epoch, prefix_bits, Clock, SystemClock, Upid};
+
+/// A distribution of Upids with a fixed prefix and the current time
+///
+/// The random bits come from the RNG passed to `sample`,
+/// so a seeded RNG gives the same Upids within the same 256ms.
+///
+/// # Example
+/// ```rust
+/// use rand::Rng;
+/// use upid::UpidDist;
+///
+/// let dist = UpidDist::new("user");
+/// let upid = rand::thread_rng().sample(&dist);
+///
+/// assert_eq!(upid.prefix(), "user");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UpidDist {
+    prefix_bits: u128,
+}
+
+impl UpidDist {
+    /// Creates a distribution for `prefix`, handled the same way as in [`Upid::from_prefix`]
+    pub fn new(prefix: &str) -> UpidDist {
+        UpidDist {
+            prefix_bits: prefix_bits(prefix),
+        }
+    }
+}
+
+impl Distribution<Upid> for UpidDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Upid {
+        let milliseconds = milliseconds_since_epoch(SystemClock.now());
+        let random = rng.gen::<u64>() as u128;
+        from_parts(milliseconds >> 8, random, self.prefix_bits)
+    }
+}
+
+/// Gives a version `a` Upid with a random timestamp, random bits and prefix
+impl Distribution<Upid> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Upid {
+        // the version character is the lowest 4 bits
+        Upid((rng.gen::<u128>() & !0xf) | prefix_bits("") & 0xf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn dist_prefix() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let dist = UpidDist::new("user");
+        let a: Upid = rng.sample(dist);
+        let b: Upid = rng.sample(dist);
+
+        assert_eq!(a.prefix(), "user");
+        assert_ne!(a, b);
+        assert_eq!(Upid::from_string(&a.to_string()), Ok(a));
+    }
+
+    #[test]
+    fn dist_seeded() {
+        let dist = UpidDist::new("user");
+        let a: Vec<Upid> = StdRng::seed_from_u64(7).sample_iter(dist).take(3).collect();
+        let b: Vec<Upid> = StdRng::seed_from_u64(7).sample_iter(dist).take(3).collect();
+
+        // only the timestamp could differ, if the clock ticked between the two
+        let strip = |v: &[Upid]| v.iter().map(|u| u.0 & ((1 << 88) - 1)).collect::<Vec<_>>();
+        assert_eq!(strip(&a), strip(&b));
+    }
+
+    #[test]
+    fn standard() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let upid: Upid = rng.gen();
+            assert_eq!(upid.version(), 'a');
+            assert_eq!(Upid::from_string(&upid.to_string()), Ok(upid));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Store Upids in DynamoDB as string or binary attributes.
+//!
+//! A Upid converts to a string (`S`) attribute by default, which sorts by prefix
+//! and then by time, so a sort key can be queried with [`begins_with`] and [`between`].
+//! Use [`to_binary_attribute`] for a binary (`B`) attribute, which is smaller
+//! and sorts by time first.
+//!
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//! use aws_sdk_dynamodb::types::AttributeValue;
+//! use upid::Upid;
+//!
+//! let upid = Upid::new("user");
+//! let value = AttributeValue::from(upid);
+//! assert_eq!(Upid::try_from(&value), Ok(upid));
+//!
+//! // For a query like `pk = :pk AND sk BETWEEN :from AND :to`
+//! let now = SystemTime::now();
+//! let (from, to) = upid::dynamodb::between("user", now - Duration::from_secs(3600), now);
+//! ```
+
+use std::fmt;
+use std::time::SystemTime;
+
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{DecodeError, Upid, UpidBuilder};
+
+/// Error when an attribute value can't be converted to a Upid
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeError {
+    /// The value isn't a string or binary attribute
+    UnexpectedType,
+    /// The string or binary isn't a valid Upid
+    Decode(DecodeError),
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::UnexpectedType => write!(f, "expected a string or binary upid"),
+            AttributeError::Decode(err) => write!(f, "invalid upid: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+impl From<Upid> for AttributeValue {
+    fn from(upid: Upid) -> AttributeValue {
+        AttributeValue::S(upid.to_string())
+    }
+}
+
+/// Converts a Upid to a binary attribute of its 16 bytes
+pub fn to_binary_attribute(upid: Upid) -> AttributeValue {
+    AttributeValue::B(Blob::new(upid.to_bytes()))
+}
+
+impl TryFrom<&AttributeValue> for Upid {
+    type Error = AttributeError;
+
+    fn try_from(value: &AttributeValue) -> Result<Upid, AttributeError> {
+        match value {
+            AttributeValue::S(text) => Upid::from_string(text).map_err(AttributeError::Decode),
+            AttributeValue::B(blob) => {
+                Upid::from_slice(blob.as_ref()).map_err(AttributeError::Decode)
+            }
+            _ => Err(AttributeError::UnexpectedType),
+        }
+    }
+}
+
+impl TryFrom<AttributeValue> for Upid {
+    type Error = AttributeError;
+
+    fn try_from(value: AttributeValue) -> Result<Upid, AttributeError> {
+        Upid::try_from(&value)
+    }
+}
+
+/// The string that every Upid with `prefix` begins with, for a `begins_with` key condition
+///
+/// The prefix is handled the same way as in [`Upid::from_prefix`].
+pub fn begins_with(prefix: &str) -> AttributeValue {
+    let prefix = UpidBuilder::new().prefix(prefix).random(0).build().prefix();
+    AttributeValue::S(format!("{prefix}_"))
+}
+
+/// The bounds of Upids with `prefix` created from `start` to `end`, for a `BETWEEN` key condition
+///
+/// Both bounds are inclusive, to the 256ms precision of the timestamp.
+pub fn between(
+    prefix: &str,
+    start: SystemTime,
+    end: SystemTime,
+) -> (AttributeValue, AttributeValue) {
+    let builder = UpidBuilder::new().prefix(prefix);
+    let lower = builder.clone().time(start).random(0).build();
+    let upper = builder.time(end).random(u64::MAX).build();
+    (lower.into(), upper.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn attribute_cycle() {
+        let want = Upid::new("user");
+
+        assert_eq!(Upid::try_from(AttributeValue::from(want)), Ok(want));
+        assert_eq!(Upid::try_from(to_binary_attribute(want)), Ok(want));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Upid::try_from(AttributeValue::S("user_short".to_string())),
+            Err(AttributeError::Decode(DecodeError::InvalidLength))
+        );
+        assert_eq!(
+            Upid::try_from(AttributeValue::N("1".to_string())),
+            Err(AttributeError::UnexpectedType)
+        );
+    }
+
+    #[test]
+    fn key_conditions() {
+        let now = SystemTime::now();
+        let upid = Upid::from_prefix_and_datetime("user", now);
+        let text = AttributeValue::from(upid);
+        let text = text.as_s().unwrap();
+
+        assert!(text.starts_with(begins_with("user").as_s().unwrap()));
+
+        let (from, to) = between("user", now - Duration::from_secs(1), now);
+        assert!(from.as_s().unwrap().as_str() <= text.as_str());
+        assert!(to.as_s().unwrap().as_str() >= text.as_str());
+
+        let (from, _) = between("user", now + Duration::from_secs(1), now);
+        assert!(from.as_s().unwrap().as_str() > text.as_str());
+    }
+}
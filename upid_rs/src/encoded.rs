@@ -0,0 +1,172 @@
+//! The string form of a Upid stored inline, for keeping many encoded Upids in memory.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::{b32, DecodeError, Upid};
+
+/// The 27-character string form of a Upid, stored inline
+///
+/// This is `Copy` and doesn't allocate, so it takes 27 bytes where a `String`
+/// takes 24 plus a heap allocation. It derefs to `&str`, and hashes and compares
+/// the same as its string, so it can key a map that is looked up by `&str`.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use upid::{EncodedUpid, Upid};
+///
+/// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+/// let encoded = EncodedUpid::from(upid);
+/// assert_eq!(&*encoded, "user_2acdrlkjmhs6ar53taem6a");
+/// assert_eq!(Upid::from(encoded), upid);
+///
+/// let mut names = HashMap::new();
+/// names.insert(encoded, "Alice");
+/// assert_eq!(names.get("user_2acdrlkjmhs6ar53taem6a"), Some(&"Alice"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EncodedUpid([u8; b32::STR_LEN]);
+
+impl EncodedUpid {
+    /// Gets the string form
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("base32 alphabet is always ASCII")
+    }
+
+    /// Gets the Upid back
+    ///
+    /// This is exact for any Upid, including ones with an unsupported version
+    /// that [`Upid::from_string`] would reject.
+    pub fn upid(&self) -> Upid {
+        let binary = b32::decode_encoded(self.as_str()).expect("encoded from a Upid");
+        Upid(binary)
+    }
+}
+
+impl From<Upid> for EncodedUpid {
+    fn from(upid: Upid) -> EncodedUpid {
+        let mut buffer = [0; b32::STR_LEN];
+        b32::encode_into(upid.0, &mut buffer);
+        EncodedUpid(buffer)
+    }
+}
+
+impl From<EncodedUpid> for Upid {
+    fn from(encoded: EncodedUpid) -> Upid {
+        encoded.upid()
+    }
+}
+
+impl TryFrom<&str> for EncodedUpid {
+    type Error = DecodeError;
+
+    /// Parses the string, storing it in the canonical form
+    fn try_from(s: &str) -> Result<EncodedUpid, DecodeError> {
+        Upid::from_string(s).map(EncodedUpid::from)
+    }
+}
+
+impl FromStr for EncodedUpid {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<EncodedUpid, DecodeError> {
+        EncodedUpid::try_from(s)
+    }
+}
+
+impl Deref for EncodedUpid {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for EncodedUpid {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for EncodedUpid {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Hash for EncodedUpid {
+    // hash as a str, so that `Borrow<str>` lookups work
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for EncodedUpid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EncodedUpid").field(&self.as_str()).finish()
+    }
+}
+
+impl fmt::Display for EncodedUpid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl Upid {
+    /// Gets the string form stored inline, see [`EncodedUpid`]
+    pub fn to_encoded(&self) -> EncodedUpid {
+        EncodedUpid::from(*self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn encoded_cycle() {
+        for upid in [Upid::new("user"), Upid::new_v2("tx"), Upid::new_v3("post")] {
+            let encoded = upid.to_encoded();
+
+            assert_eq!(encoded.as_str(), upid.to_string());
+            assert_eq!(encoded.upid(), upid);
+            assert_eq!(encoded.to_string().parse::<EncodedUpid>(), Ok(encoded));
+        }
+        assert_eq!(std::mem::size_of::<EncodedUpid>(), 27);
+    }
+
+    #[test]
+    fn encoded_unsupported_version() {
+        // eg from a UUID, which `from_string` can't parse
+        for upid in [
+            Upid(u128::MAX),
+            Upid(0),
+            Upid(0x0190_9bc6_0f93_7043_5c61_c995_24d6_1576),
+        ] {
+            let encoded = upid.to_encoded();
+
+            assert_eq!(encoded.upid(), upid);
+            assert_eq!(Upid::from(encoded), upid);
+        }
+    }
+
+    #[test]
+    fn encoded_behaves_as_str() {
+        let a = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let b = Upid::from_string("user_2acdrlkjmhs6ar53taem7a").unwrap();
+
+        assert!(a.to_encoded() < b.to_encoded());
+        assert!(a.to_encoded().starts_with("user_"));
+        assert!(HashSet::from([a.to_encoded()]).contains("user_2acdrlkjmhs6ar53taem6a"));
+        assert_eq!(
+            EncodedUpid::try_from("user_short"),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Reusable generator for minting many Upids with the same prefix.
+
+use crate::{from_parts, milliseconds_since_epoch, prefix_bits, Clock, SystemClock, Upid};
+
+/// Generates Upids for a single prefix.
+///
+/// The prefix and version are encoded once up front,
+/// so each new Upid only costs a clock read and a call to the RNG.
+///
+/// # Example
+/// ```rust
+/// use upid::PrefixedGenerator;
+///
+/// let users = PrefixedGenerator::new("user");
+/// let upid = users.generate();
+///
+/// assert_eq!(upid.prefix(), "user");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixedGenerator<C: Clock = SystemClock> {
+    prefix_bits: u128,
+    clock: C,
+}
+
+impl PrefixedGenerator {
+    /// Creates a generator for `prefix` that reads the system clock
+    ///
+    /// The prefix is handled the same way as in [`Upid::from_prefix`].
+    pub fn new(prefix: &str) -> PrefixedGenerator {
+        PrefixedGenerator::with_clock(prefix, SystemClock)
+    }
+}
+
+impl<C: Clock> PrefixedGenerator<C> {
+    /// Creates a generator for `prefix` that reads the time from `clock`
+    pub fn with_clock(prefix: &str, clock: C) -> PrefixedGenerator<C> {
+        PrefixedGenerator {
+            prefix_bits: prefix_bits(prefix),
+            clock,
+        }
+    }
+
+    /// Creates a new Upid with the current time from the generator's clock
+    pub fn generate(&self) -> Upid {
+        let milliseconds = milliseconds_since_epoch(self.clock.now());
+        self.generate_at(milliseconds)
+    }
+
+    /// Creates a new Upid at `milliseconds` since the Unix epoch, without reading the clock
+    ///
+    /// Timestamps past [`MAX_MILLISECONDS`](crate::MAX_MILLISECONDS) silently wrap around,
+    /// as in [`Upid::from_prefix_and_milliseconds`].
+    pub fn generate_at(&self, milliseconds: u128) -> Upid {
+        Upid::from_prefix_bits_and_milliseconds(self.prefix_bits, milliseconds)
+    }
+
+    /// Creates a Upid at `milliseconds` with the given 64 random bits, eg from a seeded RNG
+    ///
+    /// Timestamps wrap around as in [`PrefixedGenerator::generate_at`].
+    pub fn generate_with_random(&self, milliseconds: u128, random: u64) -> Upid {
+        from_parts(milliseconds >> 8, random as u128, self.prefix_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let users = PrefixedGenerator::new("user");
+        let a = users.generate();
+        let b = users.generate();
+
+        assert_eq!(a.prefix(), "user");
+        assert_eq!(b.prefix(), "user");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_generate_at() {
+        let users = PrefixedGenerator::new("user");
+        let upid = users.generate_at(1720600366848);
+
+        assert_eq!(upid.prefix(), "user");
+        assert_eq!(upid.milliseconds(), 1720600366848);
+
+        let upid = users.generate_with_random(1720600366848, 0x9370435c61c99524);
+        assert_eq!(upid.to_string(), "user_2acdrlkjmhs6ar53taem6a");
+    }
+
+    #[test]
+    fn test_matches_from_prefix() {
+        for prefix in ["", "ab", "user", "toolong", "00"] {
+            let want = Upid::from_prefix(prefix);
+            let got = PrefixedGenerator::new(prefix).generate();
+
+            assert_eq!(got.prefix(), want.prefix(), "{prefix}");
+            assert_eq!(got.0 & 0xff_ffff, want.0 & 0xff_ffff, "{prefix}");
+        }
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_with_clock() {
+        use crate::MockClock;
+
+        let at = SystemTime::UNIX_EPOCH + Duration::from_millis(1720600366848);
+        let users = PrefixedGenerator::with_clock("user", MockClock::new(at));
+
+        assert_eq!(users.generate().datetime(), at);
+    }
+}
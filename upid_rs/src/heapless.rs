@@ -0,0 +1,35 @@
+//! Format Upids into fixed-capacity `heapless` strings, for embedded targets.
+
+use heapless::String;
+
+use crate::Upid;
+
+impl Upid {
+    /// Gets the string representation in a stack-allocated `heapless::String`
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// assert_eq!(upid.to_heapless().as_str(), "user_2acdrlkjmhs6ar53taem6a");
+    /// ```
+    pub fn to_heapless(&self) -> String<27> {
+        let mut out = String::new();
+        self.write_to(&mut out)
+            .expect("a Upid string always fits in 27 bytes");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn heapless_matches_string() {
+        for upid in [Upid::new("user"), Upid::new_v2(""), Upid::new_v2("abcde")] {
+            assert_eq!(upid.to_heapless().as_str(), upid.to_string());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Juniper GraphQL scalar for Upids, sent as their string form.
+//!
+//! An invalid Upid is rejected with the parse error, eg
+//! "the prefix must be followed by an underscore".
+
+use juniper::{graphql_scalar, InputValue, ScalarValue, Value};
+
+#[graphql_scalar(
+    name = "Upid",
+    description = "A UPID, a prefixed and time-sortable 128-bit identifier",
+    with = upid_scalar,
+    parse_token(String)
+)]
+type Upid = crate::Upid;
+
+mod upid_scalar {
+    use super::*;
+
+    pub(super) fn to_output<S: ScalarValue>(v: &Upid) -> Value<S> {
+        Value::scalar(v.to_string())
+    }
+
+    pub(super) fn from_input<S: ScalarValue>(v: &InputValue<S>) -> Result<Upid, String> {
+        v.as_string_value()
+            .ok_or_else(|| format!("Expected `String`, found: {v}"))
+            .and_then(|s| Upid::from_string(s).map_err(|err| err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use juniper::{DefaultScalarValue, FromInputValue, ToInputValue};
+
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn from_input_value() {
+        let input: InputValue<DefaultScalarValue> = InputValue::scalar(TEXT.to_string());
+        let parsed = Upid::from_input_value(&input);
+        assert_eq!(parsed.ok(), Upid::from_string(TEXT).ok());
+
+        let invalid: InputValue<DefaultScalarValue> = InputValue::scalar("user_short".to_string());
+        let err = Upid::from_input_value(&invalid).unwrap_err();
+        let expected = Upid::from_string("user_short").unwrap_err().to_string();
+        assert_eq!(err.message(), expected);
+
+        let number: InputValue<DefaultScalarValue> = InputValue::scalar(1);
+        assert!(Upid::from_input_value(&number).is_err());
+    }
+
+    #[test]
+    fn to_input_value() {
+        let upid = Upid::from_string(TEXT).unwrap();
+        let value: InputValue<DefaultScalarValue> = upid.to_input_value();
+        assert_eq!(value, InputValue::scalar(TEXT.to_string()));
+    }
+}
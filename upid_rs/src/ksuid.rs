@@ -0,0 +1,129 @@
+//! Convert between Upid and KSUID, for migrating from segmentio-style IDs.
+//!
+//! This works on the 20 raw bytes of a KSUID (eg from `Ksuid::as_bytes` and
+//! `Ksuid::from_bytes` in the `svix-ksuid` crate) so it doesn't need any dependencies.
+//! A KSUID is a 32-bit count of seconds since 2014-05-13 followed by a 128-bit payload.
+//!
+//! Precision is lost in both directions:
+//! - KSUID seconds become Upid milliseconds, truncated to 256ms
+//! - only the first 8 bytes of the payload are kept as the Upid's random bits,
+//!   and going back they are the top 64 random bits of a Upid of any version
+//! - going back, the seconds are rounded up from the start of the Upid's 256ms window
+//!   (so KSUID -> Upid -> KSUID keeps the time) and the rest of the payload is zero
+//! - Upids from before 2014-05-13 or after 2150 are clamped to the KSUID range
+
+use crate::{from_parts, prefix_bits, Upid};
+
+/// Length of a KSUID in bytes
+const KSUID_LEN: usize = 20;
+
+/// Unix timestamp of the KSUID epoch, in seconds
+const KSUID_EPOCH: u64 = 1_400_000_000;
+
+impl Upid {
+    /// Creates a Upid from the time and payload of a KSUID, and the given prefix
+    ///
+    /// The prefix is handled the same way as in [`Upid::from_prefix`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let mut ksuid = [0xab; 20];
+    /// ksuid[..4].copy_from_slice(&320600366u32.to_be_bytes());
+    ///
+    /// let upid = Upid::from_ksuid(ksuid, "user");
+    /// assert_eq!(upid.prefix(), "user");
+    /// assert_eq!(upid.to_ksuid()[..12], ksuid[..12]);
+    /// ```
+    pub fn from_ksuid(ksuid: [u8; KSUID_LEN], prefix: &str) -> Upid {
+        let seconds = u32::from_be_bytes(ksuid[..4].try_into().expect("4 bytes")) as u64;
+        let milliseconds = (KSUID_EPOCH + seconds) as u128 * 1000;
+        let random = u64::from_be_bytes(ksuid[4..12].try_into().expect("8 bytes")) as u128;
+        from_parts(milliseconds >> 8, random, prefix_bits(prefix))
+    }
+
+    /// Converts the Upid to the 20 bytes of a KSUID with the same time and randomness
+    ///
+    /// This is lossy, see the module docs.
+    pub fn to_ksuid(&self) -> [u8; KSUID_LEN] {
+        let seconds = self.milliseconds().div_ceil(1000);
+        let seconds = seconds.saturating_sub(KSUID_EPOCH).min(u32::MAX as u64) as u32;
+        let random = self.random_u64();
+
+        let mut ksuid = [0; KSUID_LEN];
+        ksuid[..4].copy_from_slice(&seconds.to_be_bytes());
+        ksuid[4..12].copy_from_slice(&random.to_be_bytes());
+        ksuid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::UpidBuilder;
+
+    fn ksuid(seconds: u32, payload: u8) -> [u8; KSUID_LEN] {
+        let mut ksuid = [payload; KSUID_LEN];
+        ksuid[..4].copy_from_slice(&seconds.to_be_bytes());
+        ksuid
+    }
+
+    #[test]
+    fn ksuid_cycle() {
+        for seconds in [0, 1, 320600366, 320600367, u32::MAX] {
+            let original = ksuid(seconds, 0x5a);
+            let upid = Upid::from_ksuid(original, "user");
+
+            assert_eq!(upid.prefix(), "user");
+            let back = upid.to_ksuid();
+            assert_eq!(back[..12], original[..12], "{seconds}");
+            assert_eq!(back[12..], [0; 8]);
+        }
+    }
+
+    #[test]
+    fn ksuid_v2() {
+        let order = |prefix, random| {
+            UpidBuilder::new()
+                .prefix(prefix)
+                .version('b')
+                .milliseconds(1720600366848)
+                .random(random)
+                .build()
+        };
+        let upid = order("order", 0x70_435c_61c9_9524);
+
+        assert_eq!(
+            upid.to_ksuid()[4..12],
+            0x7043_5c61_c995_2400u64.to_be_bytes()
+        );
+        // the prefix isn't mixed into the payload
+        assert_eq!(
+            upid.to_ksuid(),
+            order("other", 0x70_435c_61c9_9524).to_ksuid()
+        );
+        assert_ne!(
+            upid.to_ksuid(),
+            order("order", 0x70_435c_61c9_9525).to_ksuid()
+        );
+    }
+
+    #[test]
+    fn ksuid_time() {
+        let upid = Upid::from_ksuid(ksuid(320600366, 0), "user");
+        let ms = (KSUID_EPOCH + 320600366) * 1000;
+
+        assert!(ms - upid.milliseconds() < 256);
+        assert!(Upid::from_ksuid(ksuid(320600367, 0), "user") > upid);
+    }
+
+    #[test]
+    fn ksuid_clamps() {
+        let early = Upid::from_prefix_and_milliseconds("user", 0);
+        let late = Upid::from_prefix_and_milliseconds("user", 1 << 47);
+
+        assert_eq!(early.to_ksuid()[..4], [0; 4]);
+        assert_eq!(late.to_ksuid()[..4], [0xff; 4]);
+    }
+}
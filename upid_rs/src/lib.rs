@@ -26,29 +26,170 @@
 //! - too long will be clipped to four characters
 //! ```rust
 //! use upid::Upid;
-//! let upid = Upid::new("00");
+//! let upid = Upid::from_prefix("00");
 //! assert_eq!(upid.prefix(), "zzzz");
 //! ```
+//!
+//! With the `strict-prefix` feature, [`Upid::new`] panics on an invalid prefix
+//! instead, and [`Upid::try_new`] returns an error whether or not the feature is
+//! enabled. `Upid` also stops implementing `Default`, whose empty prefix would
+//! otherwise become `zzzz`. Everything else in the crate and its bindings keeps
+//! the lenient behaviour of [`Upid::from_prefix`].
 
 // The code below is derived from the following:
 // https://github.com/dylanhart/ulid-rs
 
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "async-graphql")]
+mod async_graphql;
+#[cfg(feature = "avro")]
+pub mod avro;
+#[cfg(feature = "axum")]
+pub mod axum;
 mod b32;
+pub mod base32;
+#[cfg(feature = "borsh")]
+mod borsh;
+#[cfg(feature = "bson")]
+pub mod bson;
+mod builder;
+#[cfg(feature = "bytemuck")]
+mod bytemuck;
+mod clock;
+#[cfg(feature = "defmt")]
+mod defmt;
+#[cfg(feature = "deterministic")]
+mod deterministic;
+#[cfg(feature = "rand")]
+mod distribution;
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
+#[cfg(feature = "encoded")]
+mod encoded;
+mod generator;
+#[cfg(feature = "heapless")]
+mod heapless;
+#[cfg(feature = "juniper")]
+mod juniper;
+#[cfg(feature = "ksuid")]
+mod ksuid;
+#[cfg(feature = "monotonic")]
+mod monotonic;
+#[cfg(feature = "obfuscate")]
+pub mod obfuscate;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "polars")]
+pub mod polars;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "prost")]
+pub mod prost;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+#[cfg(feature = "rayon")]
+mod rayon;
+#[cfg(feature = "redis")]
+pub mod redis;
+mod registry;
+mod rng;
+#[cfg(feature = "rusqlite")]
+mod rusqlite;
+#[cfg(feature = "sea-orm")]
+mod sea_orm;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod short;
+#[cfg(feature = "signed")]
+mod signed;
+#[cfg(feature = "sqlx-mysql")]
+mod sqlx_mysql;
+mod stream;
+#[cfg(feature = "subtle")]
+mod subtle;
+#[cfg(feature = "test-util")]
+pub mod testing;
+mod time_ordered;
+mod typed;
+#[cfg(feature = "ulid")]
+mod ulid;
+#[cfg(feature = "utoipa")]
+mod utoipa;
 #[cfg(feature = "uuid")]
 mod uuid;
+#[cfg(feature = "zeroize")]
+mod zeroize;
 
 pub use crate::b32::{DecodeError, ENCODE};
+pub use crate::builder::{BuildError, UpidBuilder};
+#[cfg(feature = "test-util")]
+pub use crate::clock::MockClock;
+pub use crate::clock::{Clock, SystemClock};
+#[cfg(feature = "rand")]
+pub use crate::distribution::UpidDist;
+#[cfg(feature = "encoded")]
+pub use crate::encoded::EncodedUpid;
+pub use crate::generator::PrefixedGenerator;
+pub use crate::registry::PrefixRegistry;
+#[cfg(feature = "getrandom")]
+pub use crate::rng::OsEntropy;
+#[cfg(feature = "rand")]
+pub use crate::rng::RandEntropy;
+pub use crate::rng::{DefaultEntropy, EntropySource};
+pub use crate::short::ShortUpid;
+#[cfg(feature = "signed")]
+pub use crate::signed::SignedUpid;
+pub use crate::stream::UpidStream;
+pub use crate::time_ordered::TimeOrdered;
+pub use crate::typed::{TypedUpid, UpidPrefix};
+#[cfg(feature = "zeroize")]
+pub use crate::zeroize::SecretUpid;
 
 use std::fmt;
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
-use rand::Rng;
+/// The spec version used by [`Upid::new`] and friends
+pub const VERSION: char = 'a';
+
+/// The spec versions that this crate can encode and decode
+///
+/// `a` has a fixed four-character prefix, `b` has a prefix of 0 to 5 characters,
+/// and `c` is like `a` but with its time counted from [`CUSTOM_EPOCH_MILLISECONDS`].
+pub const SUPPORTED_VERSIONS: &[char] = &[VERSION, 'b', 'c'];
+
+/// The epoch of version `c` Upids, in milliseconds since the Unix epoch (1 January 2020)
+///
+/// Counting from here moves the end of the 40-bit timestamp range
+/// about fifty years later than [`MAX_MILLISECONDS`].
+pub const CUSTOM_EPOCH_MILLISECONDS: u128 = 1_577_836_800_000;
+
+/// The latest timestamp a Upid can hold, in milliseconds (2 August 10889)
+///
+/// The timestamp has 40 bits at a precision of 256ms.
+pub const MAX_MILLISECONDS: u128 = (1 << 48) - 1;
+
+/// The precision of the timestamp in a Upid
+///
+/// Upids created within the same 256ms step have the same timestamp,
+/// see [`Upid::same_time_bucket`].
+pub const TIMESTAMP_RESOLUTION: Duration = Duration::from_millis(256);
+
+/// The error for a timestamp past [`MAX_MILLISECONDS`]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct TimestampOutOfRange;
 
-const VERSION: &str = "a";
+impl std::error::Error for TimestampOutOfRange {}
 
-fn now() -> std::time::SystemTime {
-    std::time::SystemTime::now()
+impl fmt::Display for TimestampOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str("timestamp out of range")
+    }
 }
 
 /// A Upid is a unique 128-bit identifier is sortable and has a useful prefix.
@@ -58,12 +199,18 @@ fn now() -> std::time::SystemTime {
 /// In the binary, the first 40 bits are a unix timestamp with 256ms precision,
 /// the next 64 are random bits, and the last 24 are the prefix and version identifier.
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[repr(transparent)]
 pub struct Upid(pub u128);
 
 impl Upid {
     /// Creates a new Upid with the provided prefix and current time (UTC)
     ///
     /// The prefix should only contain lower-case latin alphabet characters.
+    ///
+    /// # Panics
+    /// With the `strict-prefix` feature, panics if the prefix isn't
+    /// four characters from the [`ENCODE`] alphabet.
+    ///
     /// # Example
     /// ```rust
     /// use upid::Upid;
@@ -71,9 +218,29 @@ impl Upid {
     /// let my_upid = Upid::new("user");
     /// ```
     pub fn new(prefix: &str) -> Upid {
+        #[cfg(feature = "strict-prefix")]
+        return Upid::try_new(prefix)
+            .unwrap_or_else(|err| panic!("invalid upid prefix: \"{prefix}\": {err}"));
+        #[cfg(not(feature = "strict-prefix"))]
         Upid::from_prefix(prefix)
     }
 
+    /// Creates a new Upid with the provided prefix and current time (UTC),
+    /// failing if the prefix isn't four characters from the [`ENCODE`] alphabet
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{DecodeError, Upid};
+    ///
+    /// assert_eq!(Upid::try_new("user").unwrap().prefix(), "user");
+    /// assert_eq!(Upid::try_new("us"), Err(DecodeError::InvalidLength));
+    /// assert_eq!(Upid::try_new("USER"), Err(DecodeError::InvalidChar));
+    /// ```
+    pub fn try_new(prefix: &str) -> Result<Upid, DecodeError> {
+        base32::decode_prefix(prefix)?;
+        Ok(Upid::from_prefix(prefix))
+    }
+
     /// Creates a Upid with the provided prefix and current time (UTC)
     ///
     /// The prefix should contain four lower-case latin alphabet characters.
@@ -84,7 +251,42 @@ impl Upid {
     /// let my_upid = Upid::from_prefix("user");
     /// ```
     pub fn from_prefix(prefix: &str) -> Upid {
-        Upid::from_prefix_and_datetime(prefix, now())
+        Upid::from_prefix_and_clock(prefix, &SystemClock)
+    }
+
+    /// Creates a Upid with the provided prefix and the current time from `clock`
+    ///
+    /// The prefix should only contain lower-case latin alphabet characters.
+    /// # Example
+    /// ```rust
+    /// use upid::{SystemClock, Upid};
+    ///
+    /// let my_upid = Upid::from_prefix_and_clock("user", &SystemClock);
+    /// ```
+    pub fn from_prefix_and_clock(prefix: &str, clock: &impl Clock) -> Upid {
+        Upid::from_prefix_and_datetime(prefix, clock.now())
+    }
+
+    /// Creates a Upid with the provided prefix and current time (UTC),
+    /// drawing the random bits from `entropy`
+    ///
+    /// See [`EntropySource`] for an example.
+    pub fn from_prefix_and_entropy(prefix: &str, entropy: &mut impl EntropySource) -> Upid {
+        Upid::from_prefix_clock_and_entropy(prefix, &SystemClock, entropy)
+    }
+
+    /// Creates a Upid with the provided prefix and the current time from `clock`,
+    /// drawing the random bits from `entropy`
+    pub fn from_prefix_clock_and_entropy(
+        prefix: &str,
+        clock: &impl Clock,
+        entropy: &mut impl EntropySource,
+    ) -> Upid {
+        let milliseconds = milliseconds_since_epoch(clock.now());
+        let mut bytes = [0; 8];
+        entropy.fill(&mut bytes);
+        let random = u64::from_be_bytes(bytes) as u128;
+        from_parts(milliseconds >> 8, random, prefix_bits(prefix))
     }
 
     /// Creates a new Upid with the given prefix and datetime
@@ -102,10 +304,7 @@ impl Upid {
     /// let upid = Upid::from_prefix_and_datetime("user", SystemTime::now());
     /// ```
     pub fn from_prefix_and_datetime(prefix: &str, datetime: SystemTime) -> Upid {
-        let milliseconds = datetime
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or(Duration::ZERO)
-            .as_millis();
+        let milliseconds = milliseconds_since_epoch(datetime);
         Upid::from_prefix_and_milliseconds(prefix, milliseconds)
     }
 
@@ -113,6 +312,9 @@ impl Upid {
     ///
     /// The prefix should only contain lower-case latin alphabet characters.
     ///
+    /// Timestamps past [`MAX_MILLISECONDS`] silently wrap around,
+    /// use [`Upid::checked_from_prefix_and_milliseconds`] to catch them.
+    ///
     /// # Example
     /// ```rust
     /// use upid::Upid;
@@ -121,34 +323,182 @@ impl Upid {
     /// let upid = Upid::from_prefix_and_milliseconds("user", ms);
     /// ```
     pub fn from_prefix_and_milliseconds(prefix: &str, milliseconds: u128) -> Upid {
+        Upid::from_prefix_bits_and_milliseconds(prefix_bits(prefix), milliseconds)
+    }
+
+    /// Creates a new Upid with the given prefix and timestamp in milliseconds,
+    /// or an error if the timestamp is past [`MAX_MILLISECONDS`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{TimestampOutOfRange, Upid, MAX_MILLISECONDS};
+    ///
+    /// assert!(Upid::checked_from_prefix_and_milliseconds("user", 1720568902000).is_ok());
+    /// assert_eq!(
+    ///     Upid::checked_from_prefix_and_milliseconds("user", MAX_MILLISECONDS + 1),
+    ///     Err(TimestampOutOfRange),
+    /// );
+    /// ```
+    pub fn checked_from_prefix_and_milliseconds(
+        prefix: &str,
+        milliseconds: u128,
+    ) -> Result<Upid, TimestampOutOfRange> {
+        if milliseconds > MAX_MILLISECONDS {
+            return Err(TimestampOutOfRange);
+        }
+        Ok(Upid::from_prefix_and_milliseconds(prefix, milliseconds))
+    }
+
+    /// Creates a new Upid from already-encoded prefix and version bits
+    fn from_prefix_bits_and_milliseconds(prefix_bits: u128, milliseconds: u128) -> Upid {
         // cut off the 8 lsb drops precision to 256 ms
         // future version could play with this differently
         // eg drop 4 bits on each side
         let time_bits = milliseconds >> 8;
 
         // get 64 bits of randomness on lsb side of a u128
-        let mut source = rand::thread_rng();
-        let random = source.gen::<u64>() as u128;
+        let random = rng::random_u64() as u128;
+
+        from_parts(time_bits, random, prefix_bits)
+    }
+
+    /// Creates a new Upid with the provided prefix and current time (UTC),
+    /// with the time counted from [`CUSTOM_EPOCH_MILLISECONDS`]
+    ///
+    /// This uses the version 3 (`c`) layout, which is the same as version `a` apart
+    /// from the epoch, so the timestamp runs out later. The prefix is handled the same
+    /// way as in [`Upid::from_prefix`].
+    ///
+    /// Version `a` and `c` Upids of the same prefix don't sort by time together,
+    /// so convert with [`Upid::to_unix_epoch`] or [`Upid::to_custom_epoch`] before mixing them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::new_v3("user");
+    /// assert_eq!(upid.version(), 'c');
+    /// assert_eq!(upid.prefix(), "user");
+    /// ```
+    pub fn new_v3(prefix: &str) -> Upid {
+        let milliseconds = milliseconds_since_epoch(SystemClock.now());
+        Upid::from_prefix_and_milliseconds_v3(prefix, milliseconds)
+    }
+
+    /// Creates a new version 3 Upid with the given prefix and timestamp in milliseconds
+    /// since the Unix epoch
+    ///
+    /// Times before [`CUSTOM_EPOCH_MILLISECONDS`] are clamped to it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_prefix_and_milliseconds_v3("user", 1720600366848);
+    /// assert_eq!(upid.milliseconds(), 1720600366848);
+    /// ```
+    pub fn from_prefix_and_milliseconds_v3(prefix: &str, milliseconds: u128) -> Upid {
+        let prefix_bits = (prefix_bits(prefix) & !15) | b32::VERSION_V3 as u128;
+        let milliseconds = milliseconds.saturating_sub(CUSTOM_EPOCH_MILLISECONDS);
+        Upid::from_prefix_bits_and_milliseconds(prefix_bits, milliseconds)
+    }
+
+    /// Converts a version `c` Upid to version `a`, keeping its time, prefix and random bits
+    ///
+    /// Other versions are returned as they are.
+    /// Returns `None` if the time is past [`MAX_MILLISECONDS`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::new_v3("user");
+    /// let unix = upid.to_unix_epoch().unwrap();
+    ///
+    /// assert_eq!(unix.version(), 'a');
+    /// assert_eq!(unix.milliseconds(), upid.milliseconds());
+    /// assert_eq!(unix.to_custom_epoch(), Some(upid));
+    /// ```
+    pub fn to_unix_epoch(&self) -> Option<Upid> {
+        if self.0 & 15 != b32::VERSION_V3 as u128 {
+            return Some(*self);
+        }
+        let time_bits = (self.0 >> 88) + (CUSTOM_EPOCH_MILLISECONDS >> 8);
+        let upid = Upid((self.0 & !15) | b32::DECODE[VERSION as usize] as u128);
+        upid.with_time_bits(time_bits)
+    }
+
+    /// Converts a version `a` Upid to version `c`, keeping its time, prefix and random bits
+    ///
+    /// Version `c` Upids are returned as they are.
+    /// Returns `None` for version `b`, or if the time is before [`CUSTOM_EPOCH_MILLISECONDS`].
+    pub fn to_custom_epoch(&self) -> Option<Upid> {
+        match self.version() {
+            'c' => Some(*self),
+            VERSION => {
+                let time_bits = (self.0 >> 88).checked_sub(CUSTOM_EPOCH_MILLISECONDS >> 8)?;
+                let upid = Upid((self.0 & !15) | b32::VERSION_V3 as u128);
+                upid.with_time_bits(time_bits)
+            }
+            _ => None,
+        }
+    }
+
+    /// Creates a new Upid with a prefix of up to five characters and the current time (UTC)
+    ///
+    /// This uses the version 2 (`b`) layout, which keeps the same 26 characters
+    /// but gives up five random bits for every prefix character.
+    /// A five-character prefix leaves 56 random bits, a two-character one leaves 71.
+    /// Invalid characters are replaced by 'z' and anything past five characters is clipped,
+    /// but shorter prefixes are kept as they are.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::new_v2("order");
+    /// assert_eq!(upid.prefix(), "order");
+    /// assert!(upid.to_string().starts_with("order_"));
+    /// ```
+    pub fn new_v2(prefix: &str) -> Upid {
+        let milliseconds = milliseconds_since_epoch(SystemClock.now());
+        Upid::from_prefix_and_milliseconds_v2(prefix, milliseconds)
+    }
 
-        // pad with 'z' if shorter than 4, cut to 4 if longer
-        let prefix = format!("{:z<4}", prefix);
-        let prefix: String = prefix.chars().take(4).collect();
-        let prefix = format!("{}{}", prefix, VERSION);
+    /// Creates a new version 2 Upid with the given prefix and timestamp in milliseconds
+    ///
+    /// See [`Upid::new_v2`] for how the prefix is handled.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_prefix_and_milliseconds_v2("tx", 1720568902000);
+    /// assert_eq!(upid.prefix(), "tx");
+    /// ```
+    pub fn from_prefix_and_milliseconds_v2(prefix: &str, milliseconds: u128) -> Upid {
+        let (prefix_bits, _) = b32::encode_prefix_bits_v2(prefix);
 
-        // decode_prefix Errors if the last character is past 'j' in the b32 alphabet
-        // and we control that with the VERSION variable
-        // If the prefix has characters from outside the alphabet, they will be wrapped into 'z's
-        // And we have ensured above that it is exactly 5 characters long
-        let p = b32::decode_prefix(prefix.as_bytes())
-            .expect("decode_prefix failed with version character overflow");
+        // a short prefix leaves more than 64 random bits
+        let random = ((rng::random_u64() as u128) << 64) | rng::random_u64() as u128;
 
-        let res = (time_bits << 88)
-            | (random << 24)
-            | ((p[0] as u128) << 16)
-            | ((p[1] as u128) << 8)
-            | p[2] as u128;
+        from_parts(milliseconds >> 8, random, prefix_bits)
+    }
 
-        Upid(res)
+    /// Checks that a string is a valid Upid, without decoding it
+    ///
+    /// This is cheaper than [`Upid::from_string`] when the value isn't needed,
+    /// and returns the same error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{DecodeError, Upid};
+    ///
+    /// assert_eq!(Upid::validate("user_aaccvpp5guht4dts56je5a"), Ok(()));
+    /// assert_eq!(Upid::validate("user_short"), Err(DecodeError::InvalidLength));
+    /// ```
+    pub fn validate(encoded: &str) -> Result<(), DecodeError> {
+        b32::validate(encoded)
     }
 
     /// Creates a Upid from a Base32 encoded string
@@ -169,6 +519,144 @@ impl Upid {
         }
     }
 
+    /// Decodes many strings lazily, with a result for each
+    ///
+    /// Each item is decoded on the stack, so this never allocates and a bad
+    /// item doesn't stop the rest, which suits bulk imports from CSV or JSONL.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{DecodeError, Upid};
+    ///
+    /// let lines = ["user_aaccvpp5guht4dts56je5a", "user_short"];
+    /// let results: Vec<_> = Upid::decode_many(lines.into_iter()).collect();
+    ///
+    /// assert!(results[0].is_ok());
+    /// assert_eq!(results[1], Err(DecodeError::InvalidLength));
+    /// ```
+    pub fn decode_many<'a>(
+        iter: impl Iterator<Item = &'a str> + 'a,
+    ) -> impl Iterator<Item = Result<Upid, DecodeError>> + 'a {
+        iter.map(Upid::from_string)
+    }
+
+    /// Creates a Upid from a Base32 encoded string, accepting upper-case input
+    ///
+    /// The canonical form is lower-case and [`Upid::from_string`] rejects anything else,
+    /// but IDs that have been shouted through email or ticketing systems can be recovered
+    /// with this. Note that the spec does not treat upper-case and lower-case as equivalent.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let result = Upid::from_string_lossy_case("USER_AACCVPP5GUHT4DTS56JE5A");
+    ///
+    /// assert_eq!(&result.unwrap().to_string(), "user_aaccvpp5guht4dts56je5a");
+    /// ```
+    pub fn from_string_lossy_case(encoded: &str) -> Result<Upid, DecodeError> {
+        Upid::from_string(&encoded.to_ascii_lowercase())
+    }
+
+    /// Creates a Upid from any string, without failing
+    ///
+    /// This is for ingest pipelines that must not fail on a single bad record.
+    /// Valid strings decode exactly as with [`Upid::from_string`], and anything else
+    /// is mapped to a defined Upid: case is folded, other characters outside the alphabet
+    /// become 'z', the string is padded with '2' or truncated to 26 characters, bits
+    /// that would overflow are dropped and unsupported versions become `a`.
+    /// Different bad strings can give the same Upid.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let text = "user_aaccvpp5guht4dts56je5a";
+    /// assert_eq!(Upid::from_string_lossy(text), Upid::from_string(text).unwrap());
+    ///
+    /// let mangled = Upid::from_string_lossy("us!r_aaccvpp5guht4");
+    /// assert_eq!(mangled.prefix(), "uszr");
+    /// ```
+    pub fn from_string_lossy(encoded: &str) -> Upid {
+        Upid(b32::decode_lossy(encoded))
+    }
+
+    /// Creates a Upid from a Base32 encoded string, skipping validation
+    ///
+    /// This is for tight loops re-parsing strings that were already validated,
+    /// eg with [`Upid::from_string`] at the edge of a system.
+    ///
+    /// # Safety
+    /// `encoded` must be a string that [`Upid::from_string`] accepts.
+    /// Anything else is undefined behaviour.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let text = "user_aaccvpp5guht4dts56je5a";
+    /// let checked = Upid::from_string(text).unwrap();
+    /// let unchecked = unsafe { Upid::from_string_unchecked(text) };
+    /// assert_eq!(checked, unchecked);
+    /// ```
+    pub unsafe fn from_string_unchecked(encoded: &str) -> Upid {
+        Upid(b32::decode_unchecked(encoded))
+    }
+
+    /// Creates a Upid from the upper-case form produced by [`Upid::to_string_upper`]
+    ///
+    /// Unlike [`Upid::from_string_lossy_case`] this is strict, and rejects any lower-case letters,
+    /// so each Upid has exactly one upper-case form.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let result = Upid::from_string_upper("USER_AACCVPP5GUHT4DTS56JE5A");
+    ///
+    /// assert_eq!(&result.unwrap().to_string(), "user_aaccvpp5guht4dts56je5a");
+    /// assert!(Upid::from_string_upper("user_aaccvpp5guht4dts56je5a").is_err());
+    /// ```
+    pub fn from_string_upper(encoded: &str) -> Result<Upid, DecodeError> {
+        if encoded.bytes().any(|b| b.is_ascii_lowercase()) {
+            return Err(DecodeError::InvalidChar);
+        }
+        Upid::from_string(&encoded.to_ascii_lowercase())
+    }
+
+    /// Creates a Upid from text that may have been mangled on its way to you
+    ///
+    /// Before decoding, this trims surrounding whitespace, strips wrapping quotes,
+    /// backticks or angle brackets, and strips a `urn:...:` style prefix.
+    /// A missing or extra `_` separator is also tolerated.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let want = Upid::from_string("user_aaccvpp5guht4dts56je5a").unwrap();
+    ///
+    /// assert_eq!(Upid::parse_lenient(" \"user_aaccvpp5guht4dts56je5a\"\n"), Ok(want));
+    /// assert_eq!(Upid::parse_lenient("urn:upid:useraaccvpp5guht4dts56je5a"), Ok(want));
+    /// ```
+    pub fn parse_lenient(text: &str) -> Result<Upid, DecodeError> {
+        const WRAPPERS: [(char, char); 5] =
+            [('"', '"'), ('\'', '\''), ('`', '`'), ('<', '>'), ('(', ')')];
+
+        let mut text = text.trim();
+        while let Some(inner) = WRAPPERS.iter().find_map(|&(start, end)| {
+            text.strip_prefix(start)
+                .and_then(|t| t.strip_suffix(end))
+                .map(str::trim)
+        }) {
+            text = inner;
+        }
+        if text.len() > 4 && text.as_bytes()[..4].eq_ignore_ascii_case(b"urn:") {
+            text = text.rsplit(':').next().unwrap_or(text);
+        }
+        Upid::from_string(text)
+    }
+
     /// Gets the datetime of when this Upid was created accurate to around 256ms
     ///
     /// # Example
@@ -188,6 +676,8 @@ impl Upid {
 
     /// Gets the prefix of this upid
     ///
+    /// This is always four characters, except for version 2 Upids which have 0 to 5.
+    ///
     /// # Example
     /// ```rust
     /// use upid::Upid;
@@ -198,11 +688,54 @@ impl Upid {
     /// assert_eq!(upid.prefix(), prefix);
     /// ```
     pub fn prefix(&self) -> String {
+        if b32::is_v2(self.0) {
+            return b32::encode_prefix_v2(self.0);
+        }
         let bytes: [u8; 16] = self.0.to_be_bytes();
         let (prefix, _) = b32::encode_prefix(&bytes[b32::END_RANDO_BIN..]);
         prefix
     }
 
+    /// Gets the spec version of this upid, which is the last character of its string form
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{Upid, VERSION};
+    ///
+    /// assert_eq!(Upid::new("user").version(), VERSION);
+    /// assert_eq!(Upid::new_v2("order").version(), 'b');
+    /// ```
+    pub fn version(&self) -> char {
+        ENCODE[(self.0 & 15) as usize] as char
+    }
+
+    /// Gets the random bits of this upid, shifted down to the lsb
+    ///
+    /// These are 64 bits for versions `a` and `c`, and 56 to 81 bits for version `b`,
+    /// depending on the length of its prefix.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// assert_eq!(upid.random(), 0x9370435c61c99524);
+    /// ```
+    pub fn random(&self) -> u128 {
+        let shift = b32::random_shift(self.0);
+        (self.0 >> shift) & ((1 << (88 - shift)) - 1)
+    }
+
+    /// The top 64 random bits, padded with zeroes if there are fewer, for other ID formats
+    #[cfg(any(feature = "ulid", feature = "ksuid"))]
+    fn random_u64(&self) -> u64 {
+        let bits = 88 - b32::random_shift(self.0);
+        match bits.checked_sub(64) {
+            Some(extra) => (self.random() >> extra) as u64,
+            None => (self.random() << (64 - bits)) as u64,
+        }
+    }
+
     /// Gets the timestamp section of this upid
     ///
     /// # Example
@@ -215,7 +748,110 @@ impl Upid {
     /// assert!(ms - u128::from(upid.milliseconds()) < 257);
     /// ```
     pub const fn milliseconds(&self) -> u64 {
-        ((self.0 >> 88) << 8) as u64
+        let milliseconds = ((self.0 >> 88) << 8) as u64;
+        if self.0 & 15 == b32::VERSION_V3 as u128 {
+            return milliseconds + CUSTOM_EPOCH_MILLISECONDS as u64;
+        }
+        milliseconds
+    }
+
+    /// Checks whether two Upids have the same timestamp,
+    /// ie they were created in the same [`TIMESTAMP_RESOLUTION`] step
+    ///
+    /// The times are compared after applying the epoch of version `c`,
+    /// so Upids of any version can be compared.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{Upid, TIMESTAMP_RESOLUTION};
+    ///
+    /// let ms = 1720600366848;
+    /// let upid = Upid::from_prefix_and_milliseconds("user", ms);
+    /// let later = Upid::from_prefix_and_milliseconds("post", ms + TIMESTAMP_RESOLUTION.as_millis());
+    ///
+    /// assert!(upid.same_time_bucket(&Upid::from_prefix_and_milliseconds("post", ms + 255)));
+    /// assert!(!upid.same_time_bucket(&later));
+    /// ```
+    pub const fn same_time_bucket(&self, other: &Upid) -> bool {
+        self.milliseconds() >> 8 == other.milliseconds() >> 8
+    }
+
+    /// Moves the timestamp later by `duration`, keeping the prefix and random bits
+    ///
+    /// The timestamp has a precision of 256ms, so `duration` is rounded to the nearest
+    /// multiple of that. This makes [`Upid::checked_sub_duration`] with the same
+    /// `duration` an exact inverse.
+    /// Returns `None` if the time would go past the 40-bit range
+    /// (in the year 10889, or 10939 for version `c`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use upid::Upid;
+    ///
+    /// let ttl = Duration::from_secs(3600);
+    /// let created = Upid::new("sess");
+    /// let expiry = created.checked_add_duration(ttl).unwrap();
+    ///
+    /// assert!((expiry.milliseconds() - created.milliseconds()).abs_diff(3_600_000) <= 128);
+    /// assert_eq!(expiry.prefix(), "sess");
+    /// assert_eq!(expiry.checked_sub_duration(ttl), Some(created));
+    /// ```
+    pub fn checked_add_duration(&self, duration: Duration) -> Option<Upid> {
+        let time_bits = (self.0 >> 88).checked_add(duration_bits(duration)?)?;
+        self.with_time_bits(time_bits)
+    }
+
+    /// Moves the timestamp earlier by `duration`, keeping the prefix and random bits
+    ///
+    /// As with [`Upid::checked_add_duration`], `duration` is rounded to the nearest 256ms.
+    /// Returns `None` if the time would go before the Unix epoch,
+    /// or before [`CUSTOM_EPOCH_MILLISECONDS`] for version `c`.
+    pub fn checked_sub_duration(&self, duration: Duration) -> Option<Upid> {
+        let time_bits = (self.0 >> 88).checked_sub(duration_bits(duration)?)?;
+        self.with_time_bits(time_bits)
+    }
+
+    /// Replaces the timestamp bits, or `None` if they don't fit in 40 bits
+    fn with_time_bits(&self, time_bits: u128) -> Option<Upid> {
+        if time_bits >> 40 != 0 {
+            return None;
+        }
+        Some(Upid((time_bits << 88) | (self.0 & ((1 << 88) - 1))))
+    }
+
+    /// Gets the next value in 128-bit order, or `None` for the maximum value
+    ///
+    /// This is for building exclusive bounds in keyset pagination,
+    /// where `id >= cursor.successor()` is the same as `id > cursor`.
+    /// The result usually isn't a valid Upid itself (its version bits will differ),
+    /// so compare it by its binary or bytes rather than its string form.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let cursor = Upid::new("user");
+    /// let bound = cursor.successor().unwrap();
+    ///
+    /// assert!(bound > cursor);
+    /// assert_eq!(bound.predecessor(), Some(cursor));
+    /// ```
+    pub const fn successor(&self) -> Option<Upid> {
+        match self.0.checked_add(1) {
+            Some(value) => Some(Upid(value)),
+            None => None,
+        }
+    }
+
+    /// Gets the previous value in 128-bit order, or `None` for zero
+    ///
+    /// See [`Upid::successor`], this gives the exclusive bound for `id < cursor`.
+    pub const fn predecessor(&self) -> Option<Upid> {
+        match self.0.checked_sub(1) {
+            Some(value) => Some(Upid(value)),
+            None => None,
+        }
     }
 
     /// Creates a Base32 encoded string that represents this Upid
@@ -234,41 +870,296 @@ impl Upid {
         b32::encode(self.0)
     }
 
-    /// Creates a Upid using the provided bytes array.
+    /// Writes the string representation into any [`fmt::Write`], without allocating
     ///
     /// # Example
     /// ```rust
+    /// use std::fmt::Write;
     /// use upid::Upid;
-    /// let bytes = [0xFF; 16];
     ///
-    /// let upid = Upid::from_bytes(bytes);
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// let mut out = String::from("id=");
+    /// upid.write_to(&mut out).unwrap();
+    ///
+    /// assert_eq!(out, "id=user_2acdrlkjmhs6ar53taem6a");
     /// ```
-    pub const fn from_bytes(bytes: [u8; 16]) -> Upid {
-        Self(u128::from_be_bytes(bytes))
+    pub fn write_to(&self, mut writer: impl fmt::Write) -> fmt::Result {
+        let mut buffer = [0; b32::STR_LEN];
+        writer.write_str(self.encode_to(&mut buffer))
     }
 
-    /// Returns the bytes of the Upid in big-endian order.
+    /// Encodes the Upid into a caller-provided buffer, without allocating
+    ///
+    /// Returns the encoded string, borrowed from `buffer`.
     ///
     /// # Example
     /// ```rust
-    /// use upid::Upid;
+    /// use upid::{base32, Upid};
     ///
-    /// let text = "user_aaccvpp5guht4dts56je5a";
-    /// let upid = Upid::from_string(text).unwrap();
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// let mut buffer = [0; base32::STR_LEN];
+    ///
+    /// assert_eq!(upid.encode_to(&mut buffer), "user_2acdrlkjmhs6ar53taem6a");
     /// ```
-    pub const fn to_bytes(&self) -> [u8; 16] {
-        self.0.to_be_bytes()
+    pub fn encode_to<'a>(&self, buffer: &'a mut [u8; b32::STR_LEN]) -> &'a str {
+        b32::encode_into(self.0, buffer);
+        std::str::from_utf8(buffer).expect("base32 alphabet is always ASCII")
     }
-}
-
-impl Default for Upid {
-    fn default() -> Self {
-        Upid::new("")
-    }
-}
 
-impl From<Upid> for String {
-    fn from(upid: Upid) -> String {
+    /// Appends many Upids to `out`, separated by `sep`, reserving the space up front
+    ///
+    /// This avoids an allocation for each Upid when building large `IN (...)` clauses
+    /// or CSV columns. There is no separator after the last Upid.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let ids = [
+    ///     Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap(),
+    ///     Upid::from_string("user_aaccvpp5guht4dts56je5a").unwrap(),
+    /// ];
+    /// let mut out = String::new();
+    /// Upid::encode_many(&ids, &mut out, ',');
+    ///
+    /// assert_eq!(out, "user_2acdrlkjmhs6ar53taem6a,user_aaccvpp5guht4dts56je5a");
+    /// ```
+    pub fn encode_many(ids: &[Upid], out: &mut String, sep: char) {
+        out.reserve(ids.len() * (b32::STR_LEN + sep.len_utf8()));
+        let mut buffer = [0; b32::STR_LEN];
+        for (i, upid) in ids.iter().enumerate() {
+            if i > 0 {
+                out.push(sep);
+            }
+            out.push_str(upid.encode_to(&mut buffer));
+        }
+    }
+
+    /// Gets the string representation of the Upid without the `_`
+    ///
+    /// This is for systems that strip or forbid underscores, like DNS labels.
+    /// [`Upid::from_string`] ignores underscores, so it parses this form too.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_aaccvpp5guht4dts56je5a").unwrap();
+    ///
+    /// assert_eq!(upid.to_compact_string(), "useraaccvpp5guht4dts56je5a");
+    /// assert_eq!(Upid::from_string(&upid.to_compact_string()), Ok(upid));
+    /// ```
+    pub fn to_compact_string(&self) -> String {
+        let mut buffer = [0; b32::STR_LEN];
+        self.encode_to(&mut buffer).replace('_', "")
+    }
+
+    /// Gets the upper-case string representation of the Upid
+    ///
+    /// This is useful for labels, QR codes and barcodes where upper-case alphanumerics
+    /// are more compact or scan more reliably. It is not the canonical form, so parse it
+    /// back with [`Upid::from_string_upper`] rather than [`Upid::from_string`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_aaccvpp5guht4dts56je5a").unwrap();
+    ///
+    /// assert_eq!(upid.to_string_upper(), "USER_AACCVPP5GUHT4DTS56JE5A");
+    /// ```
+    pub fn to_string_upper(&self) -> String {
+        let mut buffer = [0; b32::STR_LEN];
+        b32::encode_into(self.0, &mut buffer);
+        buffer.make_ascii_uppercase();
+        String::from_utf8(buffer.to_vec()).expect("base32 alphabet is always ASCII")
+    }
+
+    /// Gets a 26-character string that sorts by creation time
+    ///
+    /// The canonical string sorts by prefix first, which doesn't help where only
+    /// string ordering is available, like S3 key prefixes or LevelDB.
+    /// This form has the eight time characters first, then the prefix, random and
+    /// version characters in the usual order, without the `_`.
+    /// Parse it back with [`Upid::from_sortable_string`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// assert_eq!(upid.to_sortable_string(), "2acdrlkjusermhs6ar53taem6a");
+    /// ```
+    pub fn to_sortable_string(&self) -> String {
+        let buffer = b32::encode_sortable(self.0);
+        String::from_utf8(buffer.to_vec()).expect("base32 alphabet is always ASCII")
+    }
+
+    /// Creates a Upid from the form produced by [`Upid::to_sortable_string`]
+    pub fn from_sortable_string(encoded: &str) -> Result<Upid, DecodeError> {
+        b32::decode_sortable(encoded).map(Upid)
+    }
+
+    /// Creates a Upid using the provided bytes array.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    /// let bytes = [0xFF; 16];
+    ///
+    /// let upid = Upid::from_bytes(bytes);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 16]) -> Upid {
+        Self(u128::from_be_bytes(bytes))
+    }
+
+    /// Creates a Upid from a slice of big-endian bytes.
+    ///
+    /// Returns `DecodeError::InvalidLength` if the slice is not exactly 16 bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    /// let bytes: &[u8] = &[0xFF; 16];
+    ///
+    /// let upid = Upid::from_slice(bytes).unwrap();
+    /// assert!(Upid::from_slice(&bytes[..15]).is_err());
+    /// ```
+    pub fn from_slice(bytes: &[u8]) -> Result<Upid, DecodeError> {
+        let bytes: [u8; 16] = bytes.try_into().map_err(|_| DecodeError::InvalidLength)?;
+        Ok(Upid::from_bytes(bytes))
+    }
+
+    /// Returns the bytes of the Upid in big-endian order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let text = "user_aaccvpp5guht4dts56je5a";
+    /// let upid = Upid::from_string(text).unwrap();
+    /// ```
+    pub const fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Returns bytes for use as a key in ordered key-value stores
+    ///
+    /// Among Upids of the same version, these are guaranteed to sort by timestamp first
+    /// when compared byte by byte, as in RocksDB, sled or FoundationDB, so time-range
+    /// scans can use them directly.
+    /// Within the same 256ms the order is by the random bits, then the prefix.
+    /// Version `c` counts its time from [`CUSTOM_EPOCH_MILLISECONDS`], so it sorts
+    /// about fifty years early against versions `a` and `b`: keep it in its own range,
+    /// or order by [`Upid::cmp_by_time`] instead.
+    /// They are currently the same as [`Upid::to_bytes`], but this function
+    /// is the one that carries the ordering guarantee.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let early = Upid::from_prefix_and_milliseconds("zzzz", 1720568902000);
+    /// let late = Upid::from_prefix_and_milliseconds("aaaa", 1720568903000);
+    ///
+    /// assert!(early.to_key_bytes() < late.to_key_bytes());
+    /// assert_eq!(Upid::from_key_bytes(late.to_key_bytes()), late);
+    /// ```
+    pub const fn to_key_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Creates a Upid from the output of [`Upid::to_key_bytes`]
+    pub const fn from_key_bytes(bytes: [u8; 16]) -> Upid {
+        Self(u128::from_be_bytes(bytes))
+    }
+
+    /// Returns the Upid as 32 lower-case hex characters
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// assert_eq!(upid.to_hex(), "01909bc60f9370435c61c99524d61576");
+    /// ```
+    pub fn to_hex(&self) -> String {
+        format!("{:032x}", self.0)
+    }
+
+    /// Creates a Upid from 32 hex characters, in either case
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_hex("01909bc60f9370435c61c99524d61576").unwrap();
+    /// assert_eq!(upid.to_string(), "user_2acdrlkjmhs6ar53taem6a");
+    /// ```
+    pub fn from_hex(encoded: &str) -> Result<Upid, DecodeError> {
+        if encoded.len() != 32 {
+            return Err(DecodeError::InvalidLength);
+        }
+        if !encoded.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(DecodeError::InvalidChar);
+        }
+        u128::from_str_radix(encoded, 16)
+            .map(Upid)
+            .map_err(|_| DecodeError::InvalidChar)
+    }
+}
+
+/// Milliseconds since the Unix epoch, with earlier times clamped to it
+fn milliseconds_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis()
+}
+
+/// Converts a duration to a number of 256ms timestamp steps, rounding to the nearest
+fn duration_bits(duration: Duration) -> Option<u128> {
+    Some(duration.as_millis().checked_add(128)? >> 8)
+}
+
+/// Encodes a prefix and the current version into the 24 lsb of a u128
+fn prefix_bits(prefix: &str) -> u128 {
+    // pad with 'z' if shorter than 4, cut to 4 if longer
+    let prefix = format!("{:z<4}", prefix);
+    let prefix: String = prefix.chars().take(4).collect();
+    let prefix = format!("{}{}", prefix, VERSION);
+
+    // decode_prefix Errors if the last character is past 'j' in the b32 alphabet
+    // and we control that with the VERSION variable
+    // If the prefix has characters from outside the alphabet, they will be wrapped into 'z's
+    // And we have ensured above that it is exactly 5 characters long
+    let p = b32::decode_prefix(prefix.as_bytes())
+        .expect("decode_prefix failed with version character overflow");
+
+    ((p[0] as u128) << 16) | ((p[1] as u128) << 8) | p[2] as u128
+}
+
+/// Assembles a Upid from its 40 time bits, its random bits and the encoded prefix and version
+///
+/// The random bits fill the space between the time and the prefix, which depends on the
+/// version in `prefix_bits`. Time and random bits that don't fit are dropped.
+fn from_parts(time_bits: u128, random: u128, prefix_bits: u128) -> Upid {
+    let shift = b32::random_shift(prefix_bits);
+    let random = random & ((1 << (88 - shift)) - 1);
+    Upid(((time_bits & ((1 << 40) - 1)) << 88) | (random << shift) | prefix_bits)
+}
+
+/// A Upid with the `zzzz` prefix and the current time
+///
+/// Not available with the `strict-prefix` feature.
+#[cfg(not(feature = "strict-prefix"))]
+impl Default for Upid {
+    fn default() -> Self {
+        Upid::from_prefix("")
+    }
+}
+
+impl From<Upid> for String {
+    fn from(upid: Upid) -> String {
         upid.to_string()
     }
 }
@@ -285,6 +1176,47 @@ impl From<Upid> for u128 {
     }
 }
 
+/// Reinterprets the bits as two's complement, so `u128::MAX` becomes `-1`
+///
+/// Upids only have the top bit set past the year 6000 so, in practice,
+/// they sort the same way as signed integers.
+impl From<Upid> for i128 {
+    fn from(upid: Upid) -> i128 {
+        upid.0 as i128
+    }
+}
+
+/// The inverse of `From<Upid> for i128`, so every i128 maps back to a Upid
+impl From<i128> for Upid {
+    fn from(value: i128) -> Upid {
+        Upid(value as u128)
+    }
+}
+
+impl TryFrom<&[u8]> for Upid {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Upid::from_slice(bytes)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Upid {
+    type Error = DecodeError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Upid::from_slice(&bytes)
+    }
+}
+
+impl TryFrom<&str> for Upid {
+    type Error = DecodeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Upid::from_string(s)
+    }
+}
+
 impl FromStr for Upid {
     type Err = DecodeError;
 
@@ -293,9 +1225,92 @@ impl FromStr for Upid {
     }
 }
 
+impl PartialEq<str> for Upid {
+    /// Compares against the canonical string form, without allocating
+    fn eq(&self, other: &str) -> bool {
+        let mut buffer = [0; b32::STR_LEN];
+        self.encode_to(&mut buffer) == other
+    }
+}
+
+impl PartialEq<&str> for Upid {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<String> for Upid {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
 impl fmt::Display for Upid {
+    /// Formats the Upid without allocating, respecting width, fill and alignment
+    ///
+    /// `{:#}` gives the hyphenated UUID form of the same bits,
+    /// and `{:+}` the upper-case form from [`Upid::to_string_upper`].
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{}", self.to_string())
+        if f.alternate() {
+            let mut buffer = [b'-'; 36];
+            let mut digits =
+                (0..32).map(|i| b"0123456789abcdef"[(self.0 >> (124 - 4 * i)) as usize & 15]);
+            for (i, b) in buffer.iter_mut().enumerate() {
+                if ![8, 13, 18, 23].contains(&i) {
+                    *b = digits.next().expect("32 hex digits fill a UUID");
+                }
+            }
+            let text = std::str::from_utf8(&buffer).expect("hex digits are always ASCII");
+            return f.pad(text);
+        }
+
+        let mut buffer = [0; b32::STR_LEN];
+        b32::encode_into(self.0, &mut buffer);
+        if f.sign_plus() {
+            buffer.make_ascii_uppercase();
+        }
+        let text = std::str::from_utf8(&buffer).expect("base32 alphabet is always ASCII");
+        f.pad(text)
+    }
+}
+
+/// Formats every digit of the u128, so hex is always 32 characters and binary 128,
+/// then lets the formatter apply `#`, width and zero-padding
+fn fmt_digits(
+    value: u128,
+    bits_per_digit: u32,
+    digits: &[u8],
+    prefix: &str,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let mut buffer = [0; 128];
+    let len = (128 / bits_per_digit) as usize;
+    let mask = (1 << bits_per_digit) - 1;
+    for (i, b) in buffer[..len].iter_mut().rev().enumerate() {
+        *b = digits[((value >> (i as u32 * bits_per_digit)) & mask) as usize];
+    }
+    let text = std::str::from_utf8(&buffer[..len]).expect("digits are always ASCII");
+    f.pad_integral(true, prefix, text)
+}
+
+impl fmt::LowerHex for Upid {
+    /// Formats the Upid as 32 lower-case hex digits, the same as [`Upid::to_hex`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_digits(self.0, 4, b"0123456789abcdef", "0x", f)
+    }
+}
+
+impl fmt::UpperHex for Upid {
+    /// Formats the Upid as 32 upper-case hex digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_digits(self.0, 4, b"0123456789ABCDEF", "0x", f)
+    }
+}
+
+impl fmt::Binary for Upid {
+    /// Formats the Upid as 128 binary digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_digits(self.0, 1, b"01", "0b", f)
     }
 }
 
@@ -315,10 +1330,445 @@ mod tests {
         assert_eq!(Upid::from(u), want);
     }
 
+    #[test]
+    fn can_into_i128() {
+        let want = Upid::from_str("user_aaccvpp5guht4dts56je5a").unwrap();
+        let i: i128 = want.into();
+
+        assert_eq!(i as u128, want.0);
+        assert_eq!(Upid::from(i), want);
+        assert_eq!(i128::from(Upid(u128::MAX)), -1);
+        assert_eq!(Upid::from(i128::MIN), Upid(1 << 127));
+
+        let earlier = Upid::from_prefix_and_milliseconds("user", 1720600366848);
+        let later = Upid::from_prefix_and_milliseconds("user", 1720600366848 + 256);
+        assert!(i128::from(earlier) < i128::from(later));
+    }
+
+    #[test]
+    fn test_hex() {
+        let upid = Upid::new("user");
+        let hex = upid.to_hex();
+
+        assert_eq!(hex.len(), 32);
+        assert_eq!(Upid::from_hex(&hex), Ok(upid));
+        assert_eq!(Upid::from_hex(&hex.to_uppercase()), Ok(upid));
+        assert_eq!(Upid(1).to_hex(), "00000000000000000000000000000001");
+        assert_eq!(Upid::from_hex(&hex[1..]), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            Upid::from_hex("+1909bc60f9370435c61c99524d61576"),
+            Err(DecodeError::InvalidChar)
+        );
+        assert_eq!(
+            Upid::from_hex("0x909bc60f9370435c61c99524d61576"),
+            Err(DecodeError::InvalidChar)
+        );
+    }
+
+    #[test]
+    fn can_try_from_thing() {
+        let want = Upid::from_str("user_aaccvpp5guht4dts56je5a").unwrap();
+        let bytes = want.to_bytes();
+
+        assert_eq!(Upid::try_from(&bytes[..]), Ok(want));
+        assert_eq!(Upid::try_from(bytes.to_vec()), Ok(want));
+        assert_eq!(Upid::try_from(vec![0; 15]), Err(DecodeError::InvalidLength));
+        assert_eq!(Upid::from_slice(&bytes), Ok(want));
+        assert_eq!(Upid::try_from("user_aaccvpp5guht4dts56je5a"), Ok(want));
+        assert_eq!(
+            Upid::from_slice(&bytes[1..]),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(Upid::from_slice(&[0; 17]), Err(DecodeError::InvalidLength));
+    }
+
     #[test]
     fn can_display_things() {
         println!("{}", DecodeError::InvalidLength);
         println!("{}", DecodeError::InvalidChar);
+        println!("{}", DecodeError::UnsupportedVersion('z'));
+    }
+
+    #[test]
+    fn test_lossy_case() {
+        let want = Upid::new("user");
+        let text = want.to_string();
+
+        assert!(Upid::from_string(&text.to_uppercase()).is_err());
+        assert_eq!(Upid::from_string_lossy_case(&text.to_uppercase()), Ok(want));
+        assert_eq!(Upid::from_string_lossy_case(&text), Ok(want));
+    }
+
+    #[test]
+    fn test_upper() {
+        for upid in [Upid::new("user"), Upid::from_prefix(""), Upid::new_v2("tx")] {
+            let upper = upid.to_string_upper();
+
+            assert_eq!(upper, upid.to_string().to_uppercase());
+            assert_eq!(Upid::from_string_upper(&upper), Ok(upid));
+        }
+
+        let mixed = "USER_aaccvpp5guht4dts56je5a";
+        assert_eq!(
+            Upid::from_string_upper(mixed),
+            Err(DecodeError::InvalidChar)
+        );
+    }
+
+    #[test]
+    fn test_unchecked() {
+        for upid in [
+            Upid::new("user"),
+            Upid::from_prefix(""),
+            Upid::new_v2("tx"),
+            Upid::new_v2(""),
+        ] {
+            let text = upid.to_string();
+            assert_eq!(unsafe { Upid::from_string_unchecked(&text) }, upid);
+        }
+    }
+
+    #[test]
+    fn test_lossy() {
+        for upid in [Upid::new("user"), Upid::from_prefix(""), Upid::new_v2("tx")] {
+            assert_eq!(Upid::from_string_lossy(&upid.to_string()), upid);
+        }
+
+        for text in [
+            "",
+            "!",
+            "user",
+            "USER_AACCVPP5GUHT4DTS56JE5A",
+            "user_aaccvpp5guht4dts56je5aaaaaaa",
+            "user_aaccvpp5guht4dts56jezz",
+            "user_aaccvpp5guht4dts56jezb",
+            "user_aaccvpp5guht4dts56je5r",
+            "ユーザー_aaccvpp5guht4dts56je5a",
+        ] {
+            let upid = Upid::from_string_lossy(text);
+            let canonical = upid.to_string();
+            assert_eq!(
+                Upid::from_string(&canonical),
+                Ok(upid),
+                "{text} -> {canonical}"
+            );
+        }
+
+        assert_eq!(
+            Upid::from_string_lossy("USER_AACCVPP5GUHT4DTS56JE5A").to_string(),
+            "user_aaccvpp5guht4dts56je5a"
+        );
+        assert_eq!(
+            Upid::from_string_lossy("").to_string(),
+            "2222_222222222222222222222a"
+        );
+    }
+
+    #[test]
+    fn test_decode_many() {
+        let v2 = Upid::new_v2("ab");
+        let text = format!("user_2acdrlkjmhs6ar53taem6a\nuser_2acdrlkjmhs6ar53taem6z\n{v2}");
+        let results: Vec<_> = Upid::decode_many(text.lines()).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Upid::from_string("user_2acdrlkjmhs6ar53taem6a"));
+        assert_eq!(results[1], Err(DecodeError::UnsupportedVersion('z')));
+        assert_eq!(results[2], Ok(v2));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let want = Upid::from_str("user_aaccvpp5guht4dts56je5a").unwrap();
+
+        for text in [
+            "user_aaccvpp5guht4dts56je5a",
+            "  user_aaccvpp5guht4dts56je5a\t\n",
+            "'user_aaccvpp5guht4dts56je5a'",
+            "`\"user_aaccvpp5guht4dts56je5a\"`",
+            "<user_aaccvpp5guht4dts56je5a>",
+            "useraaccvpp5guht4dts56je5a",
+            "user__aaccvpp5guht4dts56je5a",
+            "URN:upid:user_aaccvpp5guht4dts56je5a",
+        ] {
+            assert_eq!(Upid::parse_lenient(text), Ok(want), "{text}");
+        }
+
+        assert!(Upid::parse_lenient("\"user_aaccvpp5guht4dts56je5a").is_err());
+        assert!(Upid::parse_lenient("urn:").is_err());
+        assert!(Upid::parse_lenient("aaa€bbbbbbbbbbbbbbbbbbbbbb").is_err());
+        assert!(Upid::parse_lenient("€").is_err());
+    }
+
+    #[test]
+    fn test_eq_str() {
+        let text = "user_2acdrlkjmhs6ar53taem6a";
+        let upid = Upid::from_string(text).unwrap();
+
+        assert_eq!(upid, text);
+        assert_eq!(upid, *text);
+        assert_eq!(upid, text.to_string());
+        assert_ne!(upid, "user_2acdrlkjmhs6ar53taem7a");
+        // only the canonical form is equal
+        assert_ne!(upid, "user2acdrlkjmhs6ar53taem6a");
+        assert_ne!(upid, "USER_2ACDRLKJMHS6AR53TAEM6A");
+    }
+
+    #[test]
+    fn test_display() {
+        let text = "user_aaccvpp5guht4dts56je5a";
+        let upid = Upid::from_str(text).unwrap();
+
+        assert_eq!(format!("{upid}"), text);
+        assert_eq!(format!("{upid:>30}"), format!("   {text}"));
+        assert_eq!(format!("{upid:-<28}|"), format!("{text}-|"));
+        assert_eq!(format!("{upid:^29}"), format!(" {text} "));
+        assert_eq!(format!("{upid:5}"), text);
+
+        assert_eq!(format!("{upid:+}"), upid.to_string_upper());
+        assert_eq!(
+            format!("{upid:>+30}"),
+            format!("   {}", upid.to_string_upper())
+        );
+
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        assert_eq!(format!("{upid:#}"), "01909bc6-0f93-7043-5c61-c99524d61576");
+        assert_eq!(
+            format!("{upid:*<#37}|"),
+            "01909bc6-0f93-7043-5c61-c99524d61576*|"
+        );
+    }
+
+    #[test]
+    fn test_hex_formatting() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let hex = "01909bc60f9370435c61c99524d61576";
+
+        assert_eq!(format!("{upid:x}"), hex);
+        assert_eq!(format!("{upid:x}"), upid.to_hex());
+        assert_eq!(format!("{upid:X}"), hex.to_uppercase());
+        assert_eq!(format!("{upid:#x}"), format!("0x{hex}"));
+        assert_eq!(format!("{upid:>34x}"), format!("  {hex}"));
+        assert_eq!(format!("{upid:b}"), format!("{:0128b}", u128::from(upid)));
+        assert_eq!(format!("{upid:#b}").len(), 130);
+    }
+
+    #[test]
+    fn test_sortable_string() {
+        for upid in [
+            Upid::new("user"),
+            Upid::new_v2(""),
+            Upid::new_v2("tx"),
+            Upid::new_v2("abcde"),
+        ] {
+            let text = upid.to_sortable_string();
+            assert_eq!(text.len(), 26);
+            assert_eq!(Upid::from_sortable_string(&text), Ok(upid));
+        }
+
+        let early = Upid::from_prefix_and_milliseconds("zzzz", 1720600366848);
+        let late = Upid::from_prefix_and_milliseconds_v2("a", 1720600367104);
+        assert!(early.to_string() > late.to_string());
+        assert!(early.to_sortable_string() < late.to_sortable_string());
+
+        assert_eq!(
+            Upid::from_sortable_string("2acdrlkjusermhs6ar53taem6"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            Upid::from_sortable_string("2acdrlkj_usermhs6ar53taem6"),
+            Err(DecodeError::InvalidChar)
+        );
+        assert_eq!(
+            Upid::from_sortable_string("2acdrlkjusermhs6ar53taem6z"),
+            Err(DecodeError::UnsupportedVersion('z'))
+        );
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Upid::try_new("user").unwrap().prefix(), "user");
+        assert_eq!(Upid::try_new(""), Err(DecodeError::InvalidLength));
+        assert_eq!(Upid::try_new("users"), Err(DecodeError::InvalidLength));
+        assert_eq!(Upid::try_new("us0r"), Err(DecodeError::InvalidChar));
+    }
+
+    #[cfg(feature = "strict-prefix")]
+    #[test]
+    #[should_panic(expected = "invalid upid prefix")]
+    fn test_strict_prefix() {
+        Upid::new("us");
+    }
+
+    #[test]
+    fn test_from_prefix_clock_and_entropy() {
+        struct Fixed(u64);
+        impl Clock for Fixed {
+            fn now(&self) -> SystemTime {
+                SystemTime::UNIX_EPOCH + Duration::from_millis(self.0)
+            }
+        }
+        struct Bits;
+        impl EntropySource for Bits {
+            fn fill(&mut self, bytes: &mut [u8; 8]) {
+                *bytes = 0x9370435c61c99524u64.to_be_bytes();
+            }
+        }
+
+        let upid = Upid::from_prefix_clock_and_entropy("user", &Fixed(1720600366848), &mut Bits);
+        assert_eq!(upid.to_string(), "user_2acdrlkjmhs6ar53taem6a");
+
+        // times past the range wrap around instead of spilling out of the time bits
+        let late = Fixed(1720600366848 + (1 << 48));
+        let upid = Upid::from_prefix_clock_and_entropy("user", &late, &mut Bits);
+        assert_eq!(upid.to_string(), "user_2acdrlkjmhs6ar53taem6a");
+    }
+
+    #[test]
+    fn test_random() {
+        let builder = UpidBuilder::new()
+            .milliseconds(1720600366848)
+            .random(0xabcd);
+
+        for prefix in ["", "tx", "order"] {
+            let upid = builder.clone().prefix(prefix).version('b').build();
+            assert_eq!(upid.random(), 0xabcd, "{prefix}");
+        }
+        assert_eq!(builder.clone().prefix("user").build().random(), 0xabcd);
+        assert_eq!(builder.version('c').build().random(), 0xabcd);
+    }
+
+    #[cfg(not(feature = "strict-prefix"))]
+    #[test]
+    fn test_default_never_panics() {
+        #[derive(Default)]
+        struct Row {
+            id: Upid,
+        }
+
+        assert_eq!(Row::default().id.prefix(), "zzzz");
+    }
+
+    #[test]
+    fn test_encode_many() {
+        let ids = [Upid::new("user"), Upid::new_v2("tx"), Upid::new_v3("post")];
+        let mut out = String::from("ids: ");
+        Upid::encode_many(&ids, &mut out, '\n');
+
+        let got: Vec<_> = out["ids: ".len()..].lines().collect();
+        assert_eq!(got, ids.map(|upid| upid.to_string()));
+
+        let mut out = String::new();
+        Upid::encode_many(&[], &mut out, ',');
+        assert_eq!(out, "");
+        Upid::encode_many(&ids[..1], &mut out, '→');
+        assert_eq!(out, ids[0].to_string());
+    }
+
+    #[test]
+    fn test_compact_string() {
+        for upid in [Upid::new("user"), Upid::new_v2("ab"), Upid::new_v2("")] {
+            let text = upid.to_compact_string();
+            assert_eq!(text.len(), 26);
+            assert!(!text.contains('_'));
+            assert_eq!(Upid::from_string(&text), Ok(upid));
+        }
+    }
+
+    #[test]
+    fn test_encode_to() {
+        let mut buffer = [0; b32::STR_LEN];
+        for upid in [Upid::new("user"), Upid::new_v2("ab"), Upid::new_v2("")] {
+            assert_eq!(upid.encode_to(&mut buffer), upid.to_string());
+        }
+    }
+
+    #[test]
+    fn test_write_to() {
+        struct Fixed([u8; 8], usize);
+
+        impl fmt::Write for Fixed {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let end = self.1 + s.len();
+                self.0
+                    .get_mut(self.1..end)
+                    .ok_or(fmt::Error)?
+                    .copy_from_slice(s.as_bytes());
+                self.1 = end;
+                Ok(())
+            }
+        }
+
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let mut out = String::new();
+        upid.write_to(&mut out).unwrap();
+        assert_eq!(out, upid.to_string());
+
+        // too small a buffer errors rather than panicking
+        assert!(upid.write_to(Fixed([0; 8], 0)).is_err());
+    }
+
+    #[test]
+    fn test_version() {
+        let upid = Upid::from_str("user_aaccvpp5guht4dts56je5a").unwrap();
+
+        assert_eq!(upid.version(), 'a');
+        assert_eq!(Upid::new_v2("tx").version(), 'b');
+        assert!(SUPPORTED_VERSIONS.contains(&Upid::new("user").version()));
+        assert!(SUPPORTED_VERSIONS.contains(&Upid::new_v2("tx").version()));
+    }
+
+    #[test]
+    fn test_supported_versions() {
+        for &version in SUPPORTED_VERSIONS {
+            let text = format!("user_aaccvpp5guht4dts56je4{version}");
+            assert_eq!(Upid::from_string(&text).map(|u| u.version()), Ok(version));
+        }
+    }
+
+    #[test]
+    fn test_v2() {
+        for prefix in ["", "a", "tx", "abc", "user", "order"] {
+            let upid = Upid::new_v2(prefix);
+            let text = upid.to_string();
+
+            assert_eq!(upid.prefix(), prefix);
+            assert_eq!(text.len(), 27, "{text}");
+            assert!(text.starts_with(&format!("{prefix}_")), "{text}");
+            assert!(text.ends_with('b'), "{text}");
+            assert_eq!(Upid::from_string(&text), Ok(upid));
+        }
+
+        assert_eq!(Upid::new_v2("orders").prefix(), "order");
+        assert_eq!(Upid::new_v2("t0").prefix(), "tz");
+    }
+
+    #[test]
+    fn test_v2_timestamp() {
+        let ms = 1720600366848;
+        let upid = Upid::from_prefix_and_milliseconds_v2("order", ms);
+        let v1 = Upid::from_prefix_and_milliseconds("user", ms);
+
+        assert_eq!(upid.milliseconds(), v1.milliseconds());
+        assert_eq!(upid.to_string()[6..14], v1.to_string()[5..13]);
+        assert_ne!(
+            Upid::from_prefix_and_milliseconds_v2("tx", ms),
+            Upid::from_prefix_and_milliseconds_v2("tx", ms)
+        );
+    }
+
+    #[test]
+    fn test_v2_rejects_padding_bit() {
+        let text = Upid::new_v2("tx").to_string();
+        let (head, tail) = text.split_at(text.len() - 2);
+        // the length char is always even, the next one up sets the padding bit
+        let odd = ENCODE[(b32_index(tail.as_bytes()[0]) + 1) as usize] as char;
+        let bad = format!("{head}{odd}b");
+
+        assert_eq!(Upid::from_string(&bad), Err(DecodeError::Overflow));
+    }
+
+    fn b32_index(c: u8) -> u8 {
+        ENCODE.iter().position(|&e| e == c).unwrap() as u8
     }
 
     #[test]
@@ -337,6 +1787,166 @@ mod tests {
         assert!(upid1 < upid2);
     }
 
+    #[test]
+    fn test_checked_milliseconds() {
+        let upid = Upid::checked_from_prefix_and_milliseconds("user", MAX_MILLISECONDS).unwrap();
+        assert_eq!(u128::from(upid.milliseconds()), MAX_MILLISECONDS - 255);
+        assert_eq!(upid.checked_add_duration(Duration::from_millis(256)), None);
+
+        assert_eq!(
+            Upid::checked_from_prefix_and_milliseconds("user", MAX_MILLISECONDS + 1),
+            Err(TimestampOutOfRange)
+        );
+        assert_eq!(
+            Upid::from_prefix_and_milliseconds("user", MAX_MILLISECONDS + 1).milliseconds(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_custom_epoch() {
+        let ms = 1720600366848;
+        let upid = Upid::from_prefix_and_milliseconds_v3("user", ms);
+        let text = upid.to_string();
+
+        assert_eq!(upid.version(), 'c');
+        assert_eq!(upid.prefix(), "user");
+        assert_eq!(u128::from(upid.milliseconds()), ms);
+        assert!(text.starts_with("user_") && text.ends_with('c'));
+        assert_eq!(Upid::from_string(&text), Ok(upid));
+        assert_eq!(Upid::validate(&text), Ok(()));
+        assert_eq!(Upid::from_string_lossy(&text), upid);
+
+        let unix = upid.to_unix_epoch().unwrap();
+        assert_eq!(unix.version(), 'a');
+        assert_eq!(unix.milliseconds(), upid.milliseconds());
+        assert_eq!(
+            u128::from(unix) >> 4 & ((1 << 84) - 1),
+            u128::from(upid) >> 4 & ((1 << 84) - 1)
+        );
+        assert_eq!(unix.to_custom_epoch(), Some(upid));
+
+        let early = Upid::from_prefix_and_milliseconds("user", 0);
+        assert_eq!(early.to_custom_epoch(), None);
+        assert_eq!(Upid::new_v2("tx").to_custom_epoch(), None);
+        assert_eq!(
+            u128::from(Upid::from_prefix_and_milliseconds_v3("user", 0).milliseconds()),
+            CUSTOM_EPOCH_MILLISECONDS
+        );
+
+        // the range runs past that of version 'a'
+        let late = Upid::from_prefix_and_milliseconds_v3("user", MAX_MILLISECONDS + 1);
+        assert_eq!(u128::from(late.milliseconds()), MAX_MILLISECONDS + 1);
+        assert_eq!(late.to_unix_epoch(), None);
+    }
+
+    #[test]
+    fn test_same_time_bucket() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let ms = u128::from(upid.milliseconds());
+        let step = TIMESTAMP_RESOLUTION.as_millis();
+
+        assert_eq!(step, 256);
+        assert!(upid.same_time_bucket(&upid));
+        assert!(upid.same_time_bucket(&Upid::new_v2("tx").with_time_bits(ms >> 8).unwrap()));
+        assert!(!upid.same_time_bucket(&Upid::from_prefix_and_milliseconds("user", ms + step)));
+        assert!(!upid.same_time_bucket(&Upid::from_prefix_and_milliseconds("user", ms - 1)));
+
+        // version 'c' is compared by its time, not its raw bits
+        let v3 = Upid::from_prefix_and_milliseconds_v3("user", ms + 255);
+        assert!(upid.same_time_bucket(&v3));
+        assert!(v3.same_time_bucket(&upid));
+        let raw = Upid::from_prefix_and_milliseconds_v3("user", ms + CUSTOM_EPOCH_MILLISECONDS);
+        assert!(!upid.same_time_bucket(&raw));
+    }
+
+    #[test]
+    fn test_checked_duration() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let hour = Duration::from_secs(3600);
+
+        let later = upid.checked_add_duration(hour).unwrap();
+        assert_eq!(later.milliseconds(), upid.milliseconds() + 3_600_128);
+        assert_eq!(
+            u128::from(later) & ((1 << 88) - 1),
+            u128::from(upid) & ((1 << 88) - 1)
+        );
+        assert_eq!(later.checked_sub_duration(hour), Some(upid));
+
+        // rounded to the 256ms precision
+        let nudged = upid
+            .checked_add_duration(Duration::from_millis(127))
+            .unwrap();
+        assert_eq!(nudged, upid);
+        let nudged = upid
+            .checked_add_duration(Duration::from_millis(128))
+            .unwrap();
+        assert_eq!(nudged.milliseconds(), upid.milliseconds() + 256);
+
+        let v2 = Upid::new_v2("tx");
+        let later = v2.checked_add_duration(hour).unwrap();
+        assert_eq!(later.prefix(), "tx");
+        assert_eq!(later.checked_sub_duration(hour), Some(v2));
+
+        assert_eq!(
+            upid.checked_sub_duration(Duration::from_secs(1 << 40)),
+            None
+        );
+        assert_eq!(
+            upid.checked_add_duration(Duration::from_secs(1 << 40)),
+            None
+        );
+        assert_eq!(upid.checked_add_duration(Duration::MAX), None);
+    }
+
+    #[test]
+    fn test_key_bytes_sort_by_time() {
+        let ms = 1720600366848;
+        let mut upids = vec![
+            Upid::from_prefix_and_milliseconds("zzzz", ms),
+            Upid::from_prefix_and_milliseconds("aaaa", ms + 256),
+            Upid::from_prefix_and_milliseconds_v2("", ms + 512),
+            Upid::from_prefix_and_milliseconds("mmmm", ms + 768),
+            Upid::from_prefix_and_milliseconds_v2("abcde", ms + 1024),
+            Upid::from_prefix_and_milliseconds("aaaa", u128::from(u32::MAX) << 16),
+        ];
+        let chronological = upids.clone();
+
+        upids.reverse();
+        upids.sort_by_key(|upid| upid.to_key_bytes());
+        assert_eq!(upids, chronological);
+
+        for upid in upids {
+            assert_eq!(Upid::from_key_bytes(upid.to_key_bytes()), upid);
+        }
+    }
+
+    #[test]
+    fn test_key_bytes_mixed_versions() {
+        let ms = 1720600366848;
+        let v3 = Upid::from_prefix_and_milliseconds_v3("user", ms);
+        let old = Upid::from_prefix_and_milliseconds("user", 157_766_400_000);
+        let later = Upid::from_prefix_and_milliseconds_v3("user", ms + 256);
+
+        // only ordered by time within a version
+        assert!(v3.to_key_bytes() < old.to_key_bytes());
+        assert_eq!(v3.cmp_by_time(&old), std::cmp::Ordering::Greater);
+        assert!(v3.to_key_bytes() < later.to_key_bytes());
+    }
+
+    #[test]
+    fn test_successor() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let next = upid.successor().unwrap();
+        let prev = upid.predecessor().unwrap();
+
+        assert!(prev < upid && upid < next);
+        assert_eq!(u128::from(next) - u128::from(prev), 2);
+        assert_eq!(next.predecessor(), Some(upid));
+        assert_eq!(Upid(u128::MAX).successor(), None);
+        assert_eq!(Upid(0).predecessor(), None);
+    }
+
     #[test]
     fn test_timestamp() {
         let dt = SystemTime::now();
@@ -1,12 +1,621 @@
-use std::env;
+//! Command-line tool for generating, inspecting and converting UPIDs
+//!
+//! Build with `cargo install upid --features cli`.
 
-use upid::Upid;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let prefix = match args.get(1) {
-        Some(value) => value,
-        None => &"".to_string(),
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use upid::{PrefixedGenerator, Upid, UpidBuilder, MAX_MILLISECONDS, VERSION};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(name = "upid", version, about = "Generate, inspect and convert UPIDs")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Output format, with one record (id, prefix, timestamp, uuid) per line for json and csv
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Plain)]
+    format: OutputFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new UPID
+    Gen {
+        /// Prefix of up to four lower-case characters
+        #[arg(short, long, default_value = "")]
+        prefix: String,
+        /// Number of UPIDs to generate, one per line
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: u64,
+        /// Use this RFC 3339 timestamp (eg 2024-07-10T08:32:46Z) instead of the current time
+        #[arg(long, value_parser = parse_rfc3339, conflicts_with = "at_ms")]
+        at: Option<SystemTime>,
+        /// Use this timestamp in milliseconds since the Unix epoch instead of the current time
+        #[arg(long)]
+        at_ms: Option<u128>,
+        /// Seed the random bits, for reproducible output
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Show the fields decoded from UPIDs
+    Inspect {
+        /// The UPIDs to inspect, read line by line from stdin if omitted
+        ids: Vec<String>,
+    },
+    /// Convert between UPID, UUID, hex and bytes forms
+    ///
+    /// Without --to, a UPID is converted to a UUID and anything else to a UPID.
+    Convert {
+        /// The IDs to convert, as a UPID, UUID, 32-character hex or byte array,
+        /// read line by line from stdin if omitted
+        ids: Vec<String>,
+        /// The form to convert to
+        #[arg(short, long)]
+        to: Option<Form>,
+        /// Replace the prefix, keeping the time and random bits, eg when converting a UUID to a UPID
+        ///
+        /// Anything but a version b or c UPID is read with the version a layout.
+        #[arg(short, long)]
+        prefix: Option<String>,
+    },
+    /// Print a shell completion script
+    ///
+    /// For example, `upid completions bash > /etc/bash_completion.d/upid`
+    Completions {
+        /// The shell to generate completions for
+        shell: Shell,
+    },
+    /// Check that UPIDs are valid, printing the valid ones
+    ///
+    /// Exits with a non-zero code if any are invalid.
+    Validate {
+        /// The UPIDs to validate, read line by line from stdin if omitted
+        ids: Vec<String>,
+    },
+}
+
+/// Number of UPIDs generated per clock read in bulk mode
+const GEN_BATCH_SIZE: u64 = 4096;
+
+/// The representations that `convert` can produce
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum Form {
+    /// user_2accvpp5guht4dts56je5a
+    Upid,
+    /// 01908dd6-a366-9b91-2738-191ea3d61576
+    Uuid,
+    /// 01908dd6a3669b912738191ea3d61576
+    Hex,
+    /// [1, 144, 141, 214, ...]
+    Bytes,
+}
+
+/// The output formats, where json and csv emit one record per UPID
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable output
+    Plain,
+    /// JSON Lines (one object per line)
+    Json,
+    /// CSV with a header row
+    Csv,
+}
+
+const CSV_HEADER: &str = "id,prefix,timestamp,uuid";
+
+fn parse_rfc3339(text: &str) -> Result<SystemTime, String> {
+    humantime::parse_rfc3339_weak(text).map_err(|err| err.to_string())
+}
+
+fn milliseconds_since_epoch(datetime: SystemTime) -> u128 {
+    datetime
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis()
+}
+
+fn parse(id: &str) -> Result<Upid, String> {
+    Upid::from_string(id).map_err(|err| format!("invalid upid \"{id}\": {err}"))
+}
+
+/// Formats the Upid timestamp as an RFC 3339 string in UTC
+///
+/// RFC 3339 stops at the year 9999, so later times are given in raw milliseconds.
+fn format_timestamp(upid: &Upid) -> String {
+    let datetime = SystemTime::UNIX_EPOCH + Duration::from_millis(upid.milliseconds());
+    let mut text = String::new();
+    match write!(text, "{}", humantime::format_rfc3339_millis(datetime)) {
+        Ok(()) => text,
+        Err(_) => upid.milliseconds().to_string(),
+    }
+}
+
+/// Renders a Upid as a json or csv record, or its plain string form
+fn record(upid: Upid, format: OutputFormat) -> String {
+    let (prefix, timestamp, uuid) = (
+        upid.prefix(),
+        format_timestamp(&upid),
+        Uuid::from(upid).hyphenated(),
+    );
+    // none of the fields can contain characters that need escaping
+    match format {
+        OutputFormat::Plain => upid.to_string(),
+        OutputFormat::Json => format!(
+            r#"{{"id":"{upid}","prefix":"{prefix}","timestamp":"{timestamp}","uuid":"{uuid}"}}"#
+        ),
+        OutputFormat::Csv => format!("{upid},{prefix},{timestamp},{uuid}"),
+    }
+}
+
+fn inspect(id: &str, format: OutputFormat) -> Result<String, String> {
+    let upid = parse(id)?;
+    if format != OutputFormat::Plain {
+        return Ok(record(upid, format));
+    }
+    // the random bits sit between time and prefix, wherever that is for the version
+    let random = upid.random();
+    let bytes: Vec<String> = upid.to_bytes().iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "prefix     {}\nversion    {}\ntimestamp  {}\nrandom     {:016x}\nuuid       {}\nbytes      {}",
+        upid.prefix(),
+        upid.version(),
+        format_timestamp(&upid),
+        random,
+        Uuid::from(upid).hyphenated(),
+        bytes.join(" "),
+    ))
+}
+
+/// Parses any of the forms in [`Form`] into a Upid
+fn parse_any(id: &str) -> Result<Upid, String> {
+    if let Ok(upid) = Upid::from_string(id) {
+        return Ok(upid);
+    }
+    if let Ok(uuid) = Uuid::parse_str(id) {
+        return Ok(Upid::from(uuid));
+    }
+    let bytes: Result<Vec<u8>, _> = id
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|b| b.trim().parse::<u8>())
+        .collect();
+    match bytes
+        .ok()
+        .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+    {
+        Some(bytes) => Ok(Upid::from_bytes(bytes)),
+        None => Err(format!(
+            "\"{id}\" is not a valid upid, uuid, hex or byte array"
+        )),
+    }
+}
+
+fn format_as(upid: Upid, form: Form) -> String {
+    match form {
+        Form::Upid => upid.to_string(),
+        Form::Uuid => Uuid::from(upid).hyphenated().to_string(),
+        Form::Hex => upid.to_hex(),
+        Form::Bytes => format!("{:?}", upid.to_bytes()),
+    }
+}
+
+/// Replaces the prefix of `upid`, keeping its time and random bits
+///
+/// Only a parsed UPID has a meaningful version, anything else is read with the `a` layout.
+fn with_prefix(upid: Upid, is_upid: bool, prefix: &str) -> Result<Upid, String> {
+    let version = match upid.version() {
+        'b' if is_upid => {
+            return Err(format!(
+                "\"{upid}\" is version b, whose random bits depend on the prefix length"
+            ))
+        }
+        'c' if is_upid => 'c',
+        _ => VERSION,
+    };
+    // only version c counts from its own epoch, other input is read as Unix time
+    let milliseconds = if is_upid {
+        upid.milliseconds() as u128
+    } else {
+        (upid.0 >> 88) << 8
     };
-    println!("{}", Upid::from_prefix(prefix).to_string());
+    UpidBuilder::new()
+        .prefix(prefix)
+        .version(version)
+        .milliseconds(milliseconds)
+        .random((upid.0 >> 24) as u64)
+        .try_build()
+        .map_err(|err| err.to_string())
+}
+
+fn convert(
+    id: &str,
+    to: Option<Form>,
+    prefix: Option<&str>,
+    format: OutputFormat,
+) -> Result<String, String> {
+    let mut upid = parse_any(id)?;
+    let is_upid = Upid::from_string(id).is_ok();
+    let to = to.unwrap_or(if is_upid { Form::Uuid } else { Form::Upid });
+    if let Some(prefix) = prefix {
+        upid = with_prefix(upid, is_upid, prefix)?;
+    }
+    match format {
+        OutputFormat::Plain => Ok(format_as(upid, to)),
+        _ => Ok(record(upid, format)),
+    }
+}
+
+/// Options for `generate` beyond the prefix and count
+struct GenOptions {
+    /// Fixed timestamp in milliseconds, otherwise the clock is read once per batch
+    milliseconds: Option<u128>,
+    /// Seeded RNG for the random bits, otherwise the library default is used
+    rng: Option<StdRng>,
+    format: OutputFormat,
+}
+
+/// Streams `count` UPIDs to `out`, reading the clock once per batch
+fn generate(
+    out: &mut impl Write,
+    prefix: &str,
+    count: u64,
+    mut options: GenOptions,
+) -> io::Result<()> {
+    // the prefix is encoded once, so each UPID only costs the random bits
+    let generator = PrefixedGenerator::new(prefix);
+    let mut remaining = count;
+    while remaining > 0 {
+        let batch = remaining.min(GEN_BATCH_SIZE);
+        let milliseconds = options
+            .milliseconds
+            .unwrap_or_else(|| milliseconds_since_epoch(SystemTime::now()));
+        for _ in 0..batch {
+            let upid = match options.rng.as_mut() {
+                Some(rng) => generator.generate_with_random(milliseconds, rng.gen()),
+                None => generator.generate_at(milliseconds),
+            };
+            match options.format {
+                OutputFormat::Plain => writeln!(out, "{upid}")?,
+                format => writeln!(out, "{}", record(upid, format))?,
+            }
+        }
+        remaining -= batch;
+    }
+    Ok(())
+}
+
+/// Treats a closed stdout (eg piped into `head`) as success
+fn ignore_broken_pipe(err: io::Error) -> Result<(), String> {
+    match err.kind() {
+        io::ErrorKind::BrokenPipe => Ok(()),
+        _ => Err(err.to_string()),
+    }
+}
+
+/// Applies `f` to each ID, or to each non-empty line of `input` if there are none
+///
+/// Results are written to `out` and errors to stderr, one per line.
+/// Returns whether every ID succeeded.
+fn for_each_id(
+    ids: Vec<String>,
+    input: impl BufRead,
+    out: &mut impl Write,
+    f: impl Fn(&str) -> Result<String, String>,
+) -> Result<bool, String> {
+    let ids: Box<dyn Iterator<Item = io::Result<String>>> = match ids.is_empty() {
+        true => Box::new(input.lines()),
+        false => Box::new(ids.into_iter().map(Ok)),
+    };
+    let mut ok = true;
+    for id in ids {
+        let id = id.map_err(|err| err.to_string())?;
+        let id = id.trim();
+        if id.is_empty() {
+            continue;
+        }
+        match f(id) {
+            Ok(text) => writeln!(out, "{text}").or_else(ignore_broken_pipe)?,
+            Err(err) => {
+                eprintln!("{err}");
+                ok = false;
+            }
+        }
+    }
+    Ok(ok)
+}
+
+/// Runs the command, returning whether all IDs were valid
+fn run(
+    command: Command,
+    format: OutputFormat,
+    input: impl BufRead,
+    out: &mut impl Write,
+) -> Result<bool, String> {
+    if format == OutputFormat::Csv && !matches!(command, Command::Completions { .. }) {
+        writeln!(out, "{CSV_HEADER}").or_else(ignore_broken_pipe)?;
+    }
+    match command {
+        Command::Gen {
+            prefix,
+            count,
+            at,
+            at_ms,
+            seed,
+        } => {
+            let milliseconds = at.map(milliseconds_since_epoch).or(at_ms);
+            if milliseconds.is_some_and(|ms| ms > MAX_MILLISECONDS) {
+                return Err(format!(
+                    "timestamp out of range: the latest is {MAX_MILLISECONDS} milliseconds"
+                ));
+            }
+            let options = GenOptions {
+                milliseconds,
+                rng: seed.map(StdRng::seed_from_u64),
+                format,
+            };
+            generate(out, &prefix, count, options)
+                .or_else(ignore_broken_pipe)
+                .map(|_| true)
+        }
+        Command::Inspect { ids } => for_each_id(ids, input, out, |id| inspect(id, format)),
+        Command::Convert { ids, to, prefix } => for_each_id(ids, input, out, |id| {
+            convert(id, to, prefix.as_deref(), format)
+        }),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "upid", out);
+            Ok(true)
+        }
+        Command::Validate { ids } => for_each_id(ids, input, out, |id| {
+            let upid = parse(id)?;
+            Ok(record(upid, format))
+        }),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut out = BufWriter::new(io::stdout().lock());
+    let res = run(cli.command, cli.format, io::stdin().lock(), &mut out)
+        .and_then(|ok| out.flush().or_else(ignore_broken_pipe).map(|_| ok));
+    match res {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+    const UUID: &str = "01909bc6-0f93-7043-5c61-c99524d61576";
+    const PLAIN: OutputFormat = OutputFormat::Plain;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut out = Vec::new();
+        let options = GenOptions {
+            milliseconds: None,
+            rng: None,
+            format: PLAIN,
+        };
+        generate(&mut out, "user", GEN_BATCH_SIZE + 10, options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.lines().count() as u64, GEN_BATCH_SIZE + 10);
+        assert!(out
+            .lines()
+            .all(|line| parse(line).unwrap().prefix() == "user"));
+    }
+
+    #[test]
+    fn test_inspect() {
+        let out = inspect(TEXT, OutputFormat::Plain).unwrap();
+        assert!(out.contains("prefix     user"));
+        assert!(out.contains("version    a"));
+        assert!(out.contains("timestamp  2024-07-10T08:32:46.848Z"));
+        assert!(out.contains("random     9370435c61c99524"));
+        assert!(out.contains(UUID));
+        assert!(out.contains("bytes      01 90 9b c6 0f 93 70 43 5c 61 c9 95 24 d6 15 76"));
+
+        let order = UpidBuilder::new()
+            .prefix("order")
+            .version('b')
+            .random(0x70435c61c99524)
+            .build();
+        let out = inspect(&order.to_string(), OutputFormat::Plain).unwrap();
+        assert!(out.contains("random     0070435c61c99524"));
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert(TEXT, None, None, PLAIN).unwrap(), UUID);
+        assert_eq!(convert(UUID, None, None, PLAIN).unwrap(), TEXT);
+        assert!(convert("nonsense", None, None, PLAIN).is_err());
+    }
+
+    #[test]
+    fn test_convert_forms() {
+        let hex = "01909bc60f9370435c61c99524d61576";
+        let bytes = "[1, 144, 155, 198, 15, 147, 112, 67, 92, 97, 201, 149, 36, 214, 21, 118]";
+
+        assert_eq!(convert(TEXT, Some(Form::Hex), None, PLAIN).unwrap(), hex);
+        assert_eq!(
+            convert(TEXT, Some(Form::Bytes), None, PLAIN).unwrap(),
+            bytes
+        );
+        assert_eq!(convert(hex, Some(Form::Upid), None, PLAIN).unwrap(), TEXT);
+        assert_eq!(convert(bytes, Some(Form::Uuid), None, PLAIN).unwrap(), UUID);
+    }
+
+    #[test]
+    fn test_convert_prefix() {
+        let got = convert(UUID, Some(Form::Upid), Some("post"), PLAIN).unwrap();
+        let got = parse(&got).unwrap();
+
+        assert_eq!(got.prefix(), "post");
+        assert_eq!(got.milliseconds(), parse(TEXT).unwrap().milliseconds());
+    }
+
+    #[test]
+    fn test_convert_prefix_versions() {
+        let v3 = UpidBuilder::new()
+            .prefix("user")
+            .version('c')
+            .milliseconds(1720600366848)
+            .build();
+        let got = convert(&v3.to_string(), Some(Form::Upid), Some("post"), PLAIN).unwrap();
+        let got = parse(&got).unwrap();
+        assert_eq!(got.prefix(), "post");
+        assert_eq!(got.version(), 'c');
+        assert_eq!(got.milliseconds(), v3.milliseconds());
+        assert_eq!(got.random(), v3.random());
+
+        let v2 = Upid::new_v2("order").to_string();
+        assert!(convert(&v2, Some(Form::Upid), Some("post"), PLAIN).is_err());
+        // the prefix is clipped, not an error
+        assert!(convert(UUID, Some(Form::Upid), Some("toolong"), PLAIN).is_ok());
+    }
+
+    #[test]
+    fn test_convert_prefix_uuid_nibble() {
+        // a UUID ending in 8 isn't a version c UPID
+        let uuid = "01909bc6-0f93-7043-5c61-c99524d61578";
+        let got = convert(uuid, Some(Form::Upid), Some("post"), PLAIN).unwrap();
+        let got = parse(&got).unwrap();
+        assert_eq!(got.version(), 'a');
+        assert_eq!(got.milliseconds(), parse(TEXT).unwrap().milliseconds());
+    }
+
+    #[test]
+    fn test_stdin() {
+        let input = format!("{TEXT}\n\nnonsense\n  {TEXT}  \n");
+        let mut out = Vec::new();
+        let command = Command::Validate { ids: vec![] };
+        let ok = run(command, PLAIN, input.as_bytes(), &mut out).unwrap();
+
+        assert!(!ok);
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{TEXT}\n{TEXT}\n"));
+    }
+
+    #[test]
+    fn test_args() {
+        let mut out = Vec::new();
+        let command = Command::Convert {
+            ids: vec![TEXT.to_string(), UUID.to_string()],
+            to: None,
+            prefix: None,
+        };
+        let ok = run(command, PLAIN, io::empty(), &mut out).unwrap();
+
+        assert!(ok);
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{UUID}\n{TEXT}\n"));
+    }
+
+    #[test]
+    fn test_formats() {
+        let want = format!(
+            r#"{{"id":"{TEXT}","prefix":"user","timestamp":"2024-07-10T08:32:46.848Z","uuid":"{UUID}"}}"#
+        );
+        assert_eq!(inspect(TEXT, OutputFormat::Json).unwrap(), want);
+
+        let mut out = Vec::new();
+        let command = Command::Validate {
+            ids: vec![TEXT.to_string()],
+        };
+        run(command, OutputFormat::Csv, io::empty(), &mut out).unwrap();
+        let want = format!("{CSV_HEADER}\n{TEXT},user,2024-07-10T08:32:46.848Z,{UUID}\n");
+        assert_eq!(String::from_utf8(out).unwrap(), want);
+    }
+
+    #[test]
+    fn test_generate_seeded() {
+        let gen = || {
+            let mut out = Vec::new();
+            let command = Command::Gen {
+                prefix: "user".to_string(),
+                count: 3,
+                at: Some(parse_rfc3339("2024-07-10T08:32:46.848Z").unwrap()),
+                at_ms: None,
+                seed: Some(42),
+            };
+            run(command, PLAIN, io::empty(), &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let out = gen();
+
+        assert_eq!(out, gen());
+        assert_eq!(
+            out.lines().collect::<std::collections::HashSet<_>>().len(),
+            3
+        );
+        for line in out.lines() {
+            assert!(line.starts_with("user_2acdrlkj"));
+        }
+    }
+
+    #[test]
+    fn test_generate_out_of_range() {
+        let command = Command::Gen {
+            prefix: "user".to_string(),
+            count: 1,
+            at: None,
+            at_ms: Some(MAX_MILLISECONDS + 1),
+            seed: None,
+        };
+        let mut out = Vec::new();
+
+        assert!(run(command, PLAIN, io::empty(), &mut out).is_err());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_generate_max_milliseconds() {
+        let command = Command::Gen {
+            prefix: "user".to_string(),
+            count: 1,
+            at: None,
+            at_ms: Some(MAX_MILLISECONDS),
+            seed: None,
+        };
+        let mut out = Vec::new();
+        run(command, OutputFormat::Csv, io::empty(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let row = out.lines().nth(1).unwrap();
+        let milliseconds = (MAX_MILLISECONDS >> 8 << 8).to_string();
+        assert_eq!(row.split(',').nth(2), Some(milliseconds.as_str()));
+
+        let upid = parse(row.split(',').next().unwrap()).unwrap();
+        let out = inspect(&upid.to_string(), PLAIN).unwrap();
+        assert!(out.contains(&format!("timestamp  {milliseconds}\n")));
+        assert!(inspect("user_zzzzzzzz2222222222222a", OutputFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn test_completions() {
+        let mut out = Vec::new();
+        let command = Command::Completions { shell: Shell::Bash };
+        run(command, OutputFormat::Csv, io::empty(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(!out.starts_with(CSV_HEADER));
+        assert!(out.contains("inspect"));
+    }
 }
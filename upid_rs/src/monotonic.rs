@@ -0,0 +1,91 @@
+//! Process-wide strictly increasing Upid generation.
+//!
+//! Every Upid from [`Upid::new_monotonic`] is greater than the one before it,
+//! across all threads and prefixes, so they can be used as event-log cursors.
+
+use portable_atomic::{AtomicU128, Ordering};
+
+use crate::Upid;
+
+/// Mask of the prefix and version bits in the binary
+const PREFIX_MASK: u128 = 0xff_ffff;
+
+/// The last Upid issued by `new_monotonic`
+static LAST: AtomicU128 = AtomicU128::new(0);
+
+impl Upid {
+    /// Creates a new Upid that is strictly greater than any previously created
+    /// by this function in the current process.
+    ///
+    /// If the clock hasn't moved on since the last call (or has gone backwards),
+    /// the random bits of the last Upid are incremented instead of drawing new ones.
+    /// This means bursts of Upids within the same 256ms are sequential rather than random,
+    /// and a very large burst can push the timestamp slightly ahead of the clock.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let first = Upid::new_monotonic("user");
+    /// let second = Upid::new_monotonic("user");
+    ///
+    /// assert!(first < second);
+    /// ```
+    pub fn new_monotonic(prefix: &str) -> Upid {
+        let candidate = Upid::from_prefix(prefix).0;
+        let mut last = LAST.load(Ordering::Relaxed);
+        loop {
+            let next = if candidate > last {
+                candidate
+            } else {
+                // bump everything above the prefix, and keep our own prefix
+                (((last >> 24) + 1) << 24) | (candidate & PREFIX_MASK)
+            };
+            match LAST.compare_exchange_weak(last, next, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Upid(next),
+                Err(actual) => last = actual,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_monotonic() {
+        let mut prev = Upid::new_monotonic("user");
+        for _ in 0..10_000 {
+            let next = Upid::new_monotonic("user");
+            assert!(next > prev);
+            assert_eq!(next.prefix(), "user");
+            prev = next;
+        }
+    }
+
+    #[test]
+    fn test_monotonic_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    (0..1000)
+                        .map(|_| Upid::new_monotonic("user"))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut all = Vec::new();
+        for handle in handles {
+            let ids = handle.join().unwrap();
+            assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            all.extend(ids);
+        }
+        let count = all.len();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), count);
+    }
+}
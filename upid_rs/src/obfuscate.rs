@@ -0,0 +1,141 @@
+//! Hide the creation time of Upids that leave the system.
+//!
+//! The 40 timestamp bits are run through a small keyed block cipher
+//! (a 10-round Feistel network over two 20-bit halves), leaving the random bits,
+//! prefix and version untouched. Anyone without the key sees a time that is
+//! effectively random, while the holder of the key can recover the original
+//! Upid, and with it the time and ordering.
+//!
+//! This is obfuscation with a small, unaudited cipher: it keeps creation times
+//! away from casual observers and scrapers, but shouldn't be relied on to keep
+//! them from a determined attacker with many samples.
+
+use crate::Upid;
+
+const ROUNDS: usize = 10;
+const HALF_BITS: u32 = 20;
+const HALF_MASK: u64 = (1 << HALF_BITS) - 1;
+const TIME_SHIFT: u32 = 88;
+const TIME_MASK: u128 = ((1 << 40) - 1) << TIME_SHIFT;
+
+/// A key for [`Upid::obfuscate`] and [`Upid::deobfuscate`]
+#[derive(Clone)]
+pub struct TimeKey {
+    round_keys: [u64; ROUNDS],
+}
+
+impl TimeKey {
+    /// Creates a key from 16 secret bytes
+    pub fn new(key: [u8; 16]) -> TimeKey {
+        let key = u128::from_be_bytes(key);
+        let (hi, lo) = ((key >> 64) as u64, key as u64);
+        let mut round_keys = [0; ROUNDS];
+        for (i, round_key) in round_keys.iter_mut().enumerate() {
+            *round_key =
+                mix(lo ^ mix(hi.wrapping_add((i as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15))));
+        }
+        TimeKey { round_keys }
+    }
+
+    fn encrypt(&self, time: u64) -> u64 {
+        let (mut left, mut right) = (time >> HALF_BITS, time & HALF_MASK);
+        for &k in &self.round_keys {
+            (left, right) = (right, left ^ (mix(k ^ right) & HALF_MASK));
+        }
+        (left << HALF_BITS) | right
+    }
+
+    fn decrypt(&self, time: u64) -> u64 {
+        let (mut left, mut right) = (time >> HALF_BITS, time & HALF_MASK);
+        for &k in self.round_keys.iter().rev() {
+            (left, right) = (right ^ (mix(k ^ left) & HALF_MASK), left);
+        }
+        (left << HALF_BITS) | right
+    }
+}
+
+impl core::fmt::Debug for TimeKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("TimeKey(..)")
+    }
+}
+
+/// The splitmix64 finalizer, used as the round function
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Upid {
+    /// Encrypts the timestamp bits of this Upid with `key`
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{obfuscate::TimeKey, Upid};
+    ///
+    /// let key = TimeKey::new(*b"sixteen byte key");
+    /// let upid = Upid::new("user");
+    /// let public = upid.obfuscate(&key);
+    ///
+    /// assert_eq!(public.prefix(), "user");
+    /// assert_eq!(public.deobfuscate(&key), upid);
+    /// ```
+    pub fn obfuscate(&self, key: &TimeKey) -> Upid {
+        self.map_time(|time| key.encrypt(time))
+    }
+
+    /// Reverses [`Upid::obfuscate`] with the same `key`
+    pub fn deobfuscate(&self, key: &TimeKey) -> Upid {
+        self.map_time(|time| key.decrypt(time))
+    }
+
+    fn map_time(&self, f: impl FnOnce(u64) -> u64) -> Upid {
+        let time = (self.0 >> TIME_SHIFT) as u64;
+        Upid((self.0 & !TIME_MASK) | ((f(time) as u128) << TIME_SHIFT))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn obfuscate_cycle() {
+        let key = TimeKey::new([7; 16]);
+        for upid in [
+            Upid::new("user"),
+            Upid::new_v2("tx"),
+            Upid(0),
+            Upid(u128::MAX),
+        ] {
+            let public = upid.obfuscate(&key);
+            assert_eq!(public.deobfuscate(&key), upid);
+            assert_eq!(public.0 & !TIME_MASK, upid.0 & !TIME_MASK);
+        }
+    }
+
+    #[test]
+    fn obfuscate_hides_time() {
+        let key = TimeKey::new([7; 16]);
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let public = upid.obfuscate(&key);
+
+        assert_ne!(public.milliseconds(), upid.milliseconds());
+        assert_ne!(public, upid.obfuscate(&TimeKey::new([8; 16])));
+        assert_eq!(public.to_string().len(), 27);
+        assert_eq!(public.prefix(), "user");
+    }
+
+    #[test]
+    fn obfuscate_is_a_permutation() {
+        let key = TimeKey::new(*b"sixteen byte key");
+        let mut seen = std::collections::HashSet::new();
+        for time in 0..10_000 {
+            let encrypted = key.encrypt(time);
+            assert!(encrypted < 1 << 40);
+            assert!(seen.insert(encrypted));
+            assert_eq!(key.decrypt(encrypted), time);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Convert between Upid and OpenTelemetry trace IDs.
+//!
+//! Both are 128 bits, so a Upid minted for a request can double as its trace ID.
+//! The bytes are copied as they are, so the trace ID's hex is the Upid's hex.
+//! Note that the rightmost bytes hold the prefix rather than random bits,
+//! so these trace IDs shouldn't be marked with the W3C `random` trace flag.
+
+use opentelemetry::trace::TraceId;
+
+use crate::Upid;
+
+impl From<Upid> for TraceId {
+    fn from(upid: Upid) -> Self {
+        TraceId::from_bytes(upid.to_bytes())
+    }
+}
+
+impl From<TraceId> for Upid {
+    fn from(trace_id: TraceId) -> Self {
+        Upid::from_bytes(trace_id.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trace_id_cycle() {
+        let want = Upid::new("user");
+        let trace_id: TraceId = want.into();
+        let got: Upid = trace_id.into();
+
+        assert_eq!(got, want);
+        assert_ne!(trace_id, TraceId::INVALID);
+    }
+
+    #[test]
+    fn trace_id_hex() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let trace_id = TraceId::from(upid);
+
+        assert_eq!(trace_id.to_string(), "01909bc60f9370435c61c99524d61576");
+    }
+}
@@ -0,0 +1,192 @@
+//! Split string Upids in a Polars `Series` into their parts, and join them back.
+//!
+//! [`to_struct`] turns a string Series into a struct Series with the fields
+//! `prefix` (string), `milliseconds` (i64, since the Unix epoch), `random` (u64),
+//! `random_high` (u64) and `version` (string), and [`from_struct`] goes the other way.
+//! Both run over the whole Series in Rust.
+//!
+//! `random` holds the lowest 64 random bits and `random_high` any above them,
+//! which only version `b` Upids with a prefix shorter than four characters have.
+//! Strings that aren't valid Upids become nulls.
+//!
+//! ```rust
+//! use polars::prelude::*;
+//!
+//! let ids = Series::new("id".into(), &["user_2acdrlkjmhs6ar53taem6a"]);
+//! let parts = upid::polars::to_struct(&ids).unwrap();
+//! assert_eq!(upid::polars::from_struct(&parts).unwrap(), ids);
+//! ```
+
+use polars::prelude::*;
+
+use crate::{Upid, UpidBuilder};
+
+// Field names of the struct Series
+const PREFIX: &str = "prefix";
+const MILLISECONDS: &str = "milliseconds";
+const RANDOM: &str = "random";
+const RANDOM_HIGH: &str = "random_high";
+const VERSION: &str = "version";
+
+/// Splits a string Series of Upids into a struct Series of their parts
+///
+/// Nulls and invalid strings become null parts.
+pub fn to_struct(series: &Series) -> PolarsResult<Series> {
+    let upids: Vec<Option<Upid>> = series
+        .str()?
+        .into_iter()
+        .map(|text| text.and_then(|text| Upid::from_string(text).ok()))
+        .collect();
+
+    let prefix: StringChunked = upids.iter().map(|upid| upid.map(|u| u.prefix())).collect();
+    let milliseconds: Int64Chunked = upids
+        .iter()
+        .map(|upid| upid.map(|u| u.milliseconds() as i64))
+        .collect();
+    let random: UInt64Chunked = upids
+        .iter()
+        .map(|upid| upid.map(|u| u.random() as u64))
+        .collect();
+    let random_high: UInt64Chunked = upids
+        .iter()
+        .map(|upid| upid.map(|u| (u.random() >> 64) as u64))
+        .collect();
+    let version: StringChunked = upids
+        .iter()
+        .map(|upid| upid.map(|u| u.version().to_string()))
+        .collect();
+
+    let fields = [
+        prefix.with_name(PREFIX.into()).into_series(),
+        milliseconds.with_name(MILLISECONDS.into()).into_series(),
+        random.with_name(RANDOM.into()).into_series(),
+        random_high.with_name(RANDOM_HIGH.into()).into_series(),
+        version.with_name(VERSION.into()).into_series(),
+    ];
+    StructChunked::from_series(series.name().clone(), series.len(), fields.iter())
+        .map(|ca| ca.into_series())
+}
+
+/// Joins a struct Series of `prefix`, `milliseconds`, `random`, `random_high` and `version`
+/// back into string Upids
+///
+/// Without a `random_high` field the high random bits are zero, and without a `version`
+/// field every row is version `a`. A row with any null part, or parts that don't make
+/// a valid Upid of its version, becomes null.
+pub fn from_struct(series: &Series) -> PolarsResult<Series> {
+    let parts = series.struct_()?;
+    let prefix = parts.field_by_name(PREFIX)?;
+    let milliseconds = parts.field_by_name(MILLISECONDS)?.cast(&DataType::Int64)?;
+    let random = parts.field_by_name(RANDOM)?.cast(&DataType::UInt64)?;
+    let random_high = match parts.field_by_name(RANDOM_HIGH) {
+        Ok(random_high) => random_high.cast(&DataType::UInt64)?,
+        Err(_) => Series::new(RANDOM_HIGH.into(), vec![0u64; series.len()]),
+    };
+    let version = match parts.field_by_name(VERSION) {
+        Ok(version) => version,
+        Err(_) => Series::new(
+            VERSION.into(),
+            vec![crate::VERSION.to_string(); series.len()],
+        ),
+    };
+
+    let upids: StringChunked = prefix
+        .str()?
+        .into_iter()
+        .zip(milliseconds.i64()?)
+        .zip(random.u64()?)
+        .zip(random_high.u64()?)
+        .zip(version.str()?)
+        .map(
+            |((((prefix, milliseconds), random), random_high), version)| {
+                let mut version = version?.chars();
+                let random = (random_high? as u128) << 64 | random? as u128;
+                let builder = UpidBuilder::new()
+                    .prefix(prefix?)
+                    .milliseconds(u128::try_from(milliseconds?).ok()?)
+                    .random_bits(random)
+                    .version(version.next().filter(|_| version.next().is_none())?);
+                Some(builder.try_build().ok()?.to_string())
+            },
+        )
+        .collect();
+    Ok(upids.with_name(series.name().clone()).into_series())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn struct_cycle() {
+        let ids = Series::new(
+            "id".into(),
+            &[
+                Some("user_2acdrlkjmhs6ar53taem6a"),
+                None,
+                Some("user_short"),
+            ],
+        );
+        let parts = to_struct(&ids).unwrap();
+        let fields = parts.struct_().unwrap();
+
+        let prefix = fields.field_by_name(PREFIX).unwrap();
+        assert_eq!(prefix.str().unwrap().get(0), Some("user"));
+        let random = fields.field_by_name(RANDOM).unwrap();
+        assert_eq!(random.u64().unwrap().get(0), Some(0x9370435c61c99524));
+        let milliseconds = fields.field_by_name(MILLISECONDS).unwrap();
+        assert_eq!(milliseconds.i64().unwrap().get(0), Some(1720600366848));
+
+        let back = from_struct(&parts).unwrap();
+        assert_eq!(back.name().as_str(), "id");
+        assert_eq!(
+            back.str().unwrap().get(0),
+            Some("user_2acdrlkjmhs6ar53taem6a")
+        );
+        assert_eq!(back.null_count(), 2);
+    }
+
+    #[test]
+    fn struct_versions() {
+        let v2 = UpidBuilder::new().prefix("order").version('b').build();
+        let v3 = Upid::new_v3("post");
+        let ids = Series::new(
+            "id".into(),
+            &[
+                v2.to_string(),
+                v3.to_string(),
+                Upid::new_v2("tx").to_string(),
+            ],
+        );
+        let parts = to_struct(&ids).unwrap();
+        let fields = parts.struct_().unwrap();
+
+        let version = fields.field_by_name(VERSION).unwrap();
+        assert_eq!(version.str().unwrap().get(1), Some("c"));
+        let milliseconds = fields.field_by_name(MILLISECONDS).unwrap();
+        assert_eq!(
+            milliseconds.i64().unwrap().get(1),
+            Some(v3.milliseconds() as i64)
+        );
+
+        let back = from_struct(&parts).unwrap();
+        assert_eq!(back, ids);
+    }
+
+    #[test]
+    fn struct_random_high() {
+        let empty = Upid::new_v2("");
+        let ids = Series::new("id".into(), &[empty.to_string()]);
+        let parts = to_struct(&ids).unwrap();
+        let fields = parts.struct_().unwrap();
+
+        let random = fields.field_by_name(RANDOM).unwrap();
+        assert_eq!(random.u64().unwrap().get(0), Some(empty.random() as u64));
+        let random_high = fields.field_by_name(RANDOM_HIGH).unwrap();
+        assert_eq!(
+            random_high.u64().unwrap().get(0),
+            Some((empty.random() >> 64) as u64)
+        );
+        assert_eq!(from_struct(&parts).unwrap(), ids);
+    }
+}
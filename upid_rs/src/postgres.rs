@@ -0,0 +1,75 @@
+//! Convert between Upid and Postgres values for `postgres`/`tokio-postgres`.
+//!
+//! A Upid can be bound to and read from both `uuid` columns and the
+//! `upid` type provided by the `upid_pg` extension. Both are sent over
+//! the wire as the 16 big-endian bytes of the Upid.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use crate::Upid;
+
+/// Name of the type created by the `upid_pg` extension
+const PG_TYPE_NAME: &str = "upid";
+
+fn accepts(ty: &Type) -> bool {
+    *ty == Type::UUID || ty.name() == PG_TYPE_NAME
+}
+
+impl<'a> FromSql<'a> for Upid {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Upid, Box<dyn Error + Sync + Send>> {
+        let bytes: [u8; 16] = raw
+            .try_into()
+            .map_err(|_| format!("invalid upid length: expected 16 bytes, got {}", raw.len()))?;
+        Ok(Upid::from_bytes(bytes))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty)
+    }
+}
+
+impl ToSql for Upid {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_slice(&self.to_bytes());
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use postgres_types::Kind;
+
+    #[test]
+    fn postgres_cycle() {
+        let want = Upid::new("user");
+        let mut buf = BytesMut::new();
+        want.to_sql(&Type::UUID, &mut buf).unwrap();
+        let got = Upid::from_sql(&Type::UUID, &buf).unwrap();
+
+        assert_eq!(got, want)
+    }
+
+    #[test]
+    fn postgres_accepts() {
+        let upid_type = Type::new(PG_TYPE_NAME.into(), 0, Kind::Simple, "public".into());
+
+        assert!(<Upid as ToSql>::accepts(&Type::UUID));
+        assert!(<Upid as FromSql>::accepts(&upid_type));
+        assert!(!<Upid as ToSql>::accepts(&Type::TEXT));
+    }
+
+    #[test]
+    fn postgres_invalid_length() {
+        assert!(Upid::from_sql(&Type::UUID, &[0; 15]).is_err());
+    }
+}
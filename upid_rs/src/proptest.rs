@@ -0,0 +1,106 @@
+//! Proptest strategies for generating Upids.
+//!
+//! These produce Upids that round-trip through [`Upid::from_string`],
+//! so property tests don't need to know anything about the bit layout.
+//!
+//! ```rust
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//! use upid::Upid;
+//!
+//! // usually inside a `proptest!` block
+//! TestRunner::default()
+//!     .run(&upid::proptest::any_upid(), |upid| {
+//!         prop_assert_eq!(Upid::from_string(&upid.to_string()), Ok(upid));
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use std::time::{Duration, SystemTime};
+
+use proptest::prelude::*;
+
+use crate::{b32, prefix_bits, Upid};
+
+/// Mask of the 40 time bits, before they're shifted into place
+const TIME_MASK: u64 = (1 << 40) - 1;
+
+/// Strategy for any valid Upid, of any supported version, prefix and timestamp
+pub fn any_upid() -> impl Strategy<Value = Upid> {
+    // versions `a` and `c` share a layout, and only differ in the epoch
+    let versions = prop::sample::select(&[b32::VERSION_V1, b32::VERSION_V3][..]);
+    let v1 = (any::<u64>(), any::<u64>(), 0..(1u32 << 20), versions).prop_map(
+        |(time, random, prefix, version)| {
+            let prefix = ((prefix as u128) << 4) | version as u128;
+            Upid(((time & TIME_MASK) as u128) << 88 | (random as u128) << 24 | prefix)
+        },
+    );
+    let v2 =
+        (any::<u64>(), any::<u128>(), 0..=b32::MAX_PREFIX_LEN_V2).prop_map(|(time, bits, len)| {
+            let bits = bits & ((1 << 81) - 1);
+            let len = (len as u128) << 4;
+            let version = b32::VERSION_V2 as u128;
+            Upid(((time & TIME_MASK) as u128) << 88 | bits << 7 | len | version)
+        });
+    prop_oneof![v1, v2]
+}
+
+/// Strategy for Upids with the given prefix and any timestamp
+///
+/// The prefix is handled the same way as in [`Upid::from_prefix`].
+pub fn upid_with_prefix(prefix: &str) -> impl Strategy<Value = Upid> {
+    let prefix = prefix_bits(prefix);
+    (any::<u64>(), any::<u64>()).prop_map(move |(time, random)| {
+        Upid(((time & TIME_MASK) as u128) << 88 | (random as u128) << 24 | prefix)
+    })
+}
+
+/// Strategy for Upids with any prefix whose [`Upid::datetime`] is in `start..end`
+///
+/// # Panics
+/// The strategy panics when sampled if the range doesn't contain
+/// a multiple of 256ms, which is the resolution of a Upid's timestamp.
+pub fn upid_in_range(start: SystemTime, end: SystemTime) -> impl Strategy<Value = Upid> {
+    let to_ms = |t: SystemTime| {
+        t.duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_millis() as u64
+    };
+    let (start, end) = (to_ms(start), to_ms(end));
+    // only timestamps that are multiples of 256ms can be represented
+    let first = start.div_ceil(256);
+    let last = end.saturating_sub(1) / 256;
+    (first..=last, any::<u64>(), 0..(1u32 << 20)).prop_map(|(time, random, prefix)| {
+        let prefix = ((prefix as u128) << 4) | b32::VERSION_V1 as u128;
+        Upid(((time & TIME_MASK) as u128) << 88 | (random as u128) << 24 | prefix)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn any_upid_round_trips(upid in any_upid()) {
+            prop_assert_eq!(Upid::from_string(&upid.to_string()), Ok(upid));
+        }
+
+        #[test]
+        fn upid_with_prefix_has_prefix(upid in upid_with_prefix("user")) {
+            prop_assert_eq!(upid.prefix(), "user");
+            prop_assert_eq!(upid.version(), 'a');
+        }
+
+        #[test]
+        fn upid_in_range_is_in_range(upid in upid_in_range(
+            SystemTime::UNIX_EPOCH + Duration::from_millis(1720600366000),
+            SystemTime::UNIX_EPOCH + Duration::from_millis(1720600367000),
+        )) {
+            let ms = upid.milliseconds();
+            prop_assert!((1720600366000..1720600367000).contains(&ms));
+            prop_assert_eq!(Upid::from_string(&upid.to_string()), Ok(upid));
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Protobuf mapping for Upids, with prost.
+//!
+//! A Upid is sent as its 16 big-endian bytes, wrapped in a message so it can't be
+//! confused with other bytes fields:
+//!
+//! ```protobuf
+//! message Upid {
+//!   bytes value = 1;
+//! }
+//! ```
+//!
+//! [`UpidMessage`] is wire-compatible with that message, so services can use it
+//! directly, or convert their own prost-generated type with
+//! [`Upid::to_proto_bytes`] and [`Upid::try_from_proto_bytes`].
+
+use bytes::Bytes;
+
+use crate::{DecodeError, Upid};
+
+/// The prost message `message Upid { bytes value = 1; }`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpidMessage {
+    /// The 16 big-endian bytes of the Upid
+    #[prost(bytes = "vec", tag = "1")]
+    pub value: Vec<u8>,
+}
+
+impl Upid {
+    /// Gets the 16 big-endian bytes for a protobuf `bytes` field
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::new("user");
+    /// let bytes = upid.to_proto_bytes();
+    ///
+    /// assert_eq!(Upid::try_from_proto_bytes(bytes), Ok(upid));
+    /// ```
+    pub fn to_proto_bytes(&self) -> Bytes {
+        Bytes::copy_from_slice(&self.to_bytes())
+    }
+
+    /// Creates a Upid from a protobuf `bytes` field
+    ///
+    /// Returns `DecodeError::InvalidLength` if there aren't exactly 16 bytes.
+    pub fn try_from_proto_bytes(bytes: Bytes) -> Result<Upid, DecodeError> {
+        Upid::from_slice(&bytes)
+    }
+}
+
+impl From<Upid> for UpidMessage {
+    fn from(upid: Upid) -> Self {
+        UpidMessage {
+            value: upid.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<UpidMessage> for Upid {
+    type Error = DecodeError;
+
+    fn try_from(message: UpidMessage) -> Result<Self, Self::Error> {
+        Upid::try_from(message.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn message_cycle() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let encoded = UpidMessage::from(upid).encode_to_vec();
+
+        // field 1, length-delimited, 16 bytes
+        assert_eq!(encoded[..2], [0x0a, 16]);
+        assert_eq!(encoded[2..], upid.to_bytes());
+
+        let decoded = UpidMessage::decode(encoded.as_slice()).unwrap();
+        assert_eq!(Upid::try_from(decoded), Ok(upid));
+    }
+
+    #[test]
+    fn message_invalid() {
+        let empty = UpidMessage::default();
+        assert_eq!(Upid::try_from(empty), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            Upid::try_from_proto_bytes(Bytes::from_static(&[0; 17])),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+}
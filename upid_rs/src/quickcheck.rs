@@ -0,0 +1,107 @@
+//! Quickcheck support, for projects not on proptest.
+//!
+//! Generated Upids are always valid, of any supported version,
+//! and shrink toward [`NIL`]: version `a` with every other bit zero.
+
+use std::iter;
+
+use quickcheck::{Arbitrary, Gen};
+
+use crate::{b32, Upid};
+
+/// Mask of the 40 time bits, before they're shifted into place
+const TIME_MASK: u64 = (1 << 40) - 1;
+
+/// Mask of the 20 prefix bits of version `a`, before they're shifted into place
+const PREFIX_MASK: u32 = (1 << 20) - 1;
+
+/// The smallest version `a` Upid, `2222_222222222222222222222a`
+const NIL: Upid = Upid(b32::VERSION_V1 as u128);
+
+impl Arbitrary for Upid {
+    fn arbitrary(g: &mut Gen) -> Upid {
+        let time = ((u64::arbitrary(g) & TIME_MASK) as u128) << 88;
+        if bool::arbitrary(g) {
+            // versions `a` and `c` share a layout, and only differ in the epoch
+            let version = *g.choose(&[b32::VERSION_V1, b32::VERSION_V3]).unwrap() as u128;
+            let random = u64::arbitrary(g) as u128;
+            let prefix = (u32::arbitrary(g) & PREFIX_MASK) as u128;
+            Upid(time | random << 24 | prefix << 4 | version)
+        } else {
+            let len = usize::arbitrary(g) % (b32::MAX_PREFIX_LEN_V2 + 1);
+            let bits = u128::arbitrary(g) & ((1 << 81) - 1);
+            Upid(time | bits << 7 | (len as u128) << 4 | b32::VERSION_V2 as u128)
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Upid>> {
+        if *self == NIL {
+            return quickcheck::empty_shrinker();
+        }
+
+        let time = (self.0 >> 88) as u64;
+        let rest = self.0 & ((1 << 88) - 1);
+        let times = time.shrink().map(move |t| Upid((t as u128) << 88 | rest));
+        let shrinks = iter::once(NIL).chain(times);
+
+        if b32::is_v2(self.0) {
+            return Box::new(shrinks);
+        }
+
+        let upper = self.0 & !((1 << 88) - 1);
+        let version = self.0 & 15;
+        let random = (self.0 >> 24) as u64;
+        let prefix = ((self.0 >> 4) as u32) & PREFIX_MASK;
+        let randoms = random
+            .shrink()
+            .map(move |r| Upid(upper | (r as u128) << 24 | (prefix as u128) << 4 | version));
+        let prefixes = prefix
+            .shrink()
+            .map(move |p| Upid(upper | (random as u128) << 24 | (p as u128) << 4 | version));
+        Box::new(shrinks.chain(randoms).chain(prefixes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quickcheck::QuickCheck;
+
+    use super::*;
+
+    #[test]
+    fn arbitrary_cycles() {
+        fn prop(upid: Upid) -> bool {
+            Upid::from_string(&upid.to_string()) == Ok(upid)
+        }
+        QuickCheck::new().quickcheck(prop as fn(Upid) -> bool);
+    }
+
+    #[test]
+    fn shrinks_are_valid() {
+        let mut g = Gen::new(100);
+        for _ in 0..20 {
+            let upid = Upid::arbitrary(&mut g);
+            for shrunk in upid.shrink().take(200) {
+                assert_eq!(Upid::from_string(&shrunk.to_string()), Ok(shrunk));
+            }
+        }
+    }
+
+    #[test]
+    fn arbitrary_covers_versions() {
+        let mut g = Gen::new(100);
+        let versions: std::collections::HashSet<char> = (0..200)
+            .map(|_| Upid::arbitrary(&mut g).version())
+            .collect();
+        assert_eq!(versions, ['a', 'b', 'c'].into());
+    }
+
+    #[test]
+    fn shrinks_to_nil() {
+        assert_eq!(NIL.to_string(), "2222_222222222222222222222a");
+        assert_eq!(NIL.shrink().next(), None);
+
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        assert_eq!(upid.shrink().next(), Some(NIL));
+    }
+}
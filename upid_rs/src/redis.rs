@@ -0,0 +1,101 @@
+//! Use Upids as Redis keys and values.
+//!
+//! A [`Upid`] is written as its 16 big-endian bytes, which is the most compact.
+//! Wrap it in [`UpidText`] to write the usual string form instead, eg for keys
+//! that people will read in `redis-cli`. Both forms can be read back into either type.
+//!
+//! ```rust,no_run
+//! use redis::Commands;
+//! use upid::{redis::UpidText, Upid};
+//!
+//! # fn main() -> redis::RedisResult<()> {
+//! let mut con = redis::Client::open("redis://127.0.0.1/")?.get_connection()?;
+//! let id = Upid::new("user");
+//! let _: () = con.set(UpidText(id), "alice")?;
+//! let _: () = con.sadd("users", id)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use redis::{ErrorKind, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value};
+
+use crate::Upid;
+
+/// A Upid that is written to Redis in its string form
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct UpidText(pub Upid);
+
+impl ToRedisArgs for Upid {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(&self.to_bytes())
+    }
+}
+
+impl ToRedisArgs for UpidText {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(self.0.to_string().as_bytes())
+    }
+}
+
+/// Reads either the 16 bytes or the string form
+fn parse(raw: &[u8]) -> RedisResult<Upid> {
+    if let Ok(upid) = Upid::from_slice(raw) {
+        return Ok(upid);
+    }
+    std::str::from_utf8(raw)
+        .ok()
+        .and_then(|text| Upid::from_string(text).ok())
+        .ok_or_else(|| (ErrorKind::TypeError, "Response was not a valid upid").into())
+}
+
+impl FromRedisValue for Upid {
+    fn from_redis_value(v: &Value) -> RedisResult<Upid> {
+        match v {
+            Value::BulkString(raw) => parse(raw),
+            Value::SimpleString(text) => parse(text.as_bytes()),
+            _ => Err((ErrorKind::TypeError, "Response type not upid compatible").into()),
+        }
+    }
+}
+
+impl FromRedisValue for UpidText {
+    fn from_redis_value(v: &Value) -> RedisResult<UpidText> {
+        Upid::from_redis_value(v).map(UpidText)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binary_cycle() {
+        let want = Upid::new("user");
+        let args = want.to_redis_args();
+
+        assert_eq!(args, vec![want.to_bytes().to_vec()]);
+        let value = Value::BulkString(args[0].clone());
+        assert_eq!(Upid::from_redis_value(&value).unwrap(), want);
+    }
+
+    #[test]
+    fn text_cycle() {
+        let want = Upid::new("user");
+        let args = UpidText(want).to_redis_args();
+
+        assert_eq!(args, vec![want.to_string().into_bytes()]);
+        let value = Value::BulkString(args[0].clone());
+        assert_eq!(UpidText::from_redis_value(&value).unwrap(), UpidText(want));
+        assert_eq!(Upid::from_redis_value(&value).unwrap(), want);
+
+        let value = Value::SimpleString(want.to_string());
+        assert_eq!(Upid::from_redis_value(&value).unwrap(), want);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Upid::from_redis_value(&Value::BulkString(vec![0; 15])).is_err());
+        assert!(Upid::from_redis_value(&Value::Int(1)).is_err());
+        assert!(Upid::from_redis_value(&Value::Nil).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! The set of prefixes an application knows about, checked at its boundary.
+
+use std::collections::BTreeSet;
+
+use crate::{DecodeError, TypedUpid, Upid, UpidPrefix};
+
+/// The prefixes that an application accepts
+///
+/// Parsing through the registry rejects Upids with any other prefix, so IDs from
+/// elsewhere (or typos) are caught where they come in. Prefixes are matched against
+/// [`Upid::prefix`], so version `a` prefixes should be four characters.
+///
+/// # Example
+/// ```rust
+/// use upid::{prefix_registry, DecodeError};
+///
+/// let registry = prefix_registry!["user", "ordr"];
+///
+/// assert!(registry.parse("user_2acdrlkjmhs6ar53taem6a").is_ok());
+/// assert_eq!(
+///     registry.parse("acct_2acdrlkjmhs6ar53taem6a"),
+///     Err(DecodeError::UnexpectedPrefix),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixRegistry {
+    prefixes: BTreeSet<String>,
+}
+
+impl PrefixRegistry {
+    /// Creates an empty registry
+    pub fn new() -> PrefixRegistry {
+        PrefixRegistry::default()
+    }
+
+    /// Adds a prefix to the registry
+    pub fn register(mut self, prefix: &str) -> PrefixRegistry {
+        self.prefixes.insert(prefix.to_string());
+        self
+    }
+
+    /// Adds the prefix of a [`TypedUpid`] to the registry
+    pub fn register_typed<P: UpidPrefix>(self) -> PrefixRegistry {
+        self.register(P::PREFIX)
+    }
+
+    /// Checks whether a prefix is registered
+    pub fn contains(&self, prefix: &str) -> bool {
+        self.prefixes.contains(prefix)
+    }
+
+    /// Gets the registered prefixes, in order
+    pub fn prefixes(&self) -> impl Iterator<Item = &str> {
+        self.prefixes.iter().map(String::as_str)
+    }
+
+    /// Checks that a Upid's prefix is registered
+    pub fn check(&self, upid: Upid) -> Result<Upid, DecodeError> {
+        match self.contains(&upid.prefix()) {
+            true => Ok(upid),
+            false => Err(DecodeError::UnexpectedPrefix),
+        }
+    }
+
+    /// Parses a Upid, rejecting it if its prefix isn't registered
+    pub fn parse(&self, encoded: &str) -> Result<Upid, DecodeError> {
+        self.check(Upid::from_string(encoded)?)
+    }
+
+    /// Parses a Upid as a [`TypedUpid`], which must also be registered
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::{DecodeError, PrefixRegistry, UpidPrefix};
+    ///
+    /// struct User;
+    /// impl UpidPrefix for User {
+    ///     const PREFIX: &'static str = "user";
+    /// }
+    ///
+    /// let registry = PrefixRegistry::new().register_typed::<User>();
+    /// let user = registry.parse_typed::<User>("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// assert_eq!(user.upid().prefix(), "user");
+    /// ```
+    pub fn parse_typed<P: UpidPrefix>(&self, encoded: &str) -> Result<TypedUpid<P>, DecodeError> {
+        TypedUpid::from_upid(self.parse(encoded)?)
+    }
+}
+
+/// Builds a [`PrefixRegistry`] from a list of prefixes
+///
+/// ```rust
+/// let registry = upid::prefix_registry!["user", "ordr"];
+/// assert!(registry.contains("ordr"));
+/// ```
+#[macro_export]
+macro_rules! prefix_registry {
+    ($($prefix:expr),* $(,)?) => {
+        $crate::PrefixRegistry::new()$(.register($prefix))*
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Order;
+
+    impl UpidPrefix for Order {
+        const PREFIX: &'static str = "ordr";
+    }
+
+    #[test]
+    fn registry_parse() {
+        let registry = prefix_registry!["user", "tx"].register_typed::<Order>();
+        assert_eq!(
+            registry.prefixes().collect::<Vec<_>>(),
+            ["ordr", "tx", "user"]
+        );
+
+        let user = Upid::new("user");
+        assert_eq!(registry.parse(&user.to_string()), Ok(user));
+
+        let tx = Upid::new_v2("tx");
+        assert_eq!(registry.parse(&tx.to_string()), Ok(tx));
+
+        let acct = Upid::new("acct").to_string();
+        assert_eq!(registry.parse(&acct), Err(DecodeError::UnexpectedPrefix));
+        assert_eq!(registry.parse("user"), Err(DecodeError::InvalidLength));
+    }
+
+    #[test]
+    fn registry_typed() {
+        let registry = prefix_registry!["user"];
+        let order = Upid::new("ordr").to_string();
+
+        // the type must be registered as well as match
+        assert_eq!(
+            registry.parse_typed::<Order>(&order),
+            Err(DecodeError::UnexpectedPrefix)
+        );
+
+        let registry = registry.register_typed::<Order>();
+        assert!(registry.parse_typed::<Order>(&order).is_ok());
+        assert_eq!(
+            registry.parse_typed::<Order>(&Upid::new("user").to_string()),
+            Err(DecodeError::UnexpectedPrefix)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Sources of randomness for Upid generation.
+//!
+//! One of the `rand` (default), `getrandom` or `fastrand` features must be enabled.
+//! `rand` pulls in the most dependencies, `getrandom` reads directly from the OS,
+//! and `fastrand` is the smallest but is not cryptographically secure.
+//!
+//! The `osrng` feature takes precedence over all of these and draws every Upid's
+//! random bits directly from the operating system's CSPRNG via `rand::rngs::OsRng`.
+//! Use it when Upids are handed out as hard-to-guess tokens and you need that
+//! guarantee documented rather than relying on `thread_rng`'s current implementation.
+//!
+//! [`EntropySource`] lets a single Upid draw its random bits from somewhere else,
+//! like a seeded `rand` RNG or a microcontroller's hardware RNG.
+
+#[cfg(not(any(feature = "rand", feature = "getrandom", feature = "fastrand")))]
+compile_error!("upid requires one of the `rand`, `getrandom` or `fastrand` features");
+
+/// Gets 64 random bits, from the seeded generator while a test has frozen generation
+pub fn random_u64() -> u64 {
+    #[cfg(feature = "test-util")]
+    if let Some(random) = crate::testing::frozen_random_u64() {
+        return random;
+    }
+    backend_u64()
+}
+
+/// Gets 64 random bits from the preferred enabled backend
+#[cfg(feature = "osrng")]
+fn backend_u64() -> u64 {
+    use rand::Rng;
+    rand::rngs::OsRng.gen()
+}
+
+/// Gets 64 random bits from the preferred enabled backend
+#[cfg(all(not(feature = "osrng"), feature = "rand"))]
+fn backend_u64() -> u64 {
+    use rand::Rng;
+    rand::thread_rng().gen()
+}
+
+/// Gets 64 random bits from the preferred enabled backend
+#[cfg(all(not(feature = "rand"), feature = "getrandom"))]
+fn backend_u64() -> u64 {
+    let mut bytes = [0u8; 8];
+    getrandom::getrandom(&mut bytes).expect("getrandom failed to read OS randomness");
+    u64::from_ne_bytes(bytes)
+}
+
+/// Gets 64 random bits from the preferred enabled backend
+#[cfg(all(
+    not(feature = "rand"),
+    not(feature = "getrandom"),
+    feature = "fastrand"
+))]
+fn backend_u64() -> u64 {
+    fastrand::u64(..)
+}
+
+/// A source of random bits for Upid generation
+///
+/// Implement this for hardware RNG peripherals that aren't a `rand` RNG,
+/// and pass it to [`Upid::from_prefix_and_entropy`](crate::Upid::from_prefix_and_entropy).
+///
+/// # Example
+/// ```rust
+/// use upid::{EntropySource, Upid};
+///
+/// struct Trng; // eg a microcontroller's RNG peripheral
+///
+/// impl EntropySource for Trng {
+///     fn fill(&mut self, bytes: &mut [u8; 8]) {
+///         *bytes = [4; 8]; // read the peripheral here
+///     }
+/// }
+///
+/// let upid = Upid::from_prefix_and_entropy("user", &mut Trng);
+/// assert_eq!(upid.prefix(), "user");
+/// ```
+pub trait EntropySource {
+    /// Fills `bytes` with random bits
+    fn fill(&mut self, bytes: &mut [u8; 8]);
+}
+
+/// The backend chosen by the enabled features, as used by [`Upid::new`](crate::Upid::new)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultEntropy;
+
+impl EntropySource for DefaultEntropy {
+    fn fill(&mut self, bytes: &mut [u8; 8]) {
+        *bytes = random_u64().to_ne_bytes();
+    }
+}
+
+/// Any `rand` RNG, eg a seeded one for reproducible simulations
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub struct RandEntropy<R: rand::RngCore>(pub R);
+
+#[cfg(feature = "rand")]
+impl<R: rand::RngCore> EntropySource for RandEntropy<R> {
+    fn fill(&mut self, bytes: &mut [u8; 8]) {
+        self.0.fill_bytes(bytes);
+    }
+}
+
+/// The operating system's RNG, read directly through `getrandom`
+#[cfg(feature = "getrandom")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsEntropy;
+
+#[cfg(feature = "getrandom")]
+impl EntropySource for OsEntropy {
+    fn fill(&mut self, bytes: &mut [u8; 8]) {
+        getrandom::getrandom(bytes).expect("getrandom failed to read OS randomness");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_u64() {
+        assert_ne!(random_u64(), random_u64());
+    }
+
+    #[test]
+    fn test_entropy_sources() {
+        let mut bytes = [0; 8];
+        DefaultEntropy.fill(&mut bytes);
+        assert_ne!(bytes, [0; 8]);
+
+        #[cfg(feature = "rand")]
+        {
+            use rand::SeedableRng;
+            let mut a = RandEntropy(rand::rngs::StdRng::seed_from_u64(7));
+            let mut b = RandEntropy(rand::rngs::StdRng::seed_from_u64(7));
+            let (mut x, mut y) = ([0; 8], [0; 8]);
+            a.fill(&mut x);
+            b.fill(&mut y);
+            assert_eq!(x, y);
+        }
+
+        #[cfg(feature = "getrandom")]
+        {
+            let mut bytes = [0; 8];
+            OsEntropy.fill(&mut bytes);
+            assert_ne!(bytes, [0; 8]);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Convert between Upid and SQLite values for `rusqlite`.
+//!
+//! A Upid is written as a 16-byte big-endian BLOB, so it sorts by time.
+//! It can be read back from either a BLOB(16) or the TEXT encoding.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+use crate::Upid;
+
+impl ToSql for Upid {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_bytes().to_vec()))
+    }
+}
+
+impl FromSql for Upid {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Blob(blob) => {
+                let bytes: [u8; 16] =
+                    blob.try_into().map_err(|_| FromSqlError::InvalidBlobSize {
+                        expected_size: 16,
+                        blob_size: blob.len(),
+                    })?;
+                Ok(Upid::from_bytes(bytes))
+            }
+            ValueRef::Text(text) => {
+                let text =
+                    std::str::from_utf8(text).map_err(|err| FromSqlError::Other(err.into()))?;
+                Upid::from_string(text).map_err(|err| FromSqlError::Other(err.into()))
+            }
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn rusqlite_cycle() {
+        let conn = Connection::open_in_memory().unwrap();
+        let want = Upid::new("user");
+
+        let got: Upid = conn
+            .query_row("SELECT ?1", [want], |row| row.get(0))
+            .unwrap();
+        assert_eq!(got, want);
+
+        let got: Upid = conn
+            .query_row("SELECT ?1", [want.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn rusqlite_invalid() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let res = conn.query_row("SELECT x'0102'", [], |row| row.get::<_, Upid>(0));
+        assert!(res.is_err());
+
+        let res = conn.query_row("SELECT 'user_invalid'", [], |row| row.get::<_, Upid>(0));
+        assert!(res.is_err());
+    }
+}
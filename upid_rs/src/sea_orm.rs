@@ -0,0 +1,69 @@
+//! Use Upid as a column type in SeaORM entities.
+//!
+//! A Upid is stored as a `uuid` column, so it works with Postgres
+//! (including the `upid` type via its implicit casts), MySQL and SQLite.
+
+use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, Value, ValueType, ValueTypeErr};
+use sea_orm::{ColIdx, QueryResult, TryGetError, TryGetable};
+use uuid::Uuid;
+
+use crate::Upid;
+
+impl From<Upid> for Value {
+    fn from(upid: Upid) -> Self {
+        Value::Uuid(Some(Box::new(upid.into())))
+    }
+}
+
+impl ValueType for Upid {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::Uuid(Some(uuid)) => Ok((*uuid).into()),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Upid".to_owned()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::Uuid
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::Uuid
+    }
+}
+
+impl Nullable for Upid {
+    fn null() -> Value {
+        Value::Uuid(None)
+    }
+}
+
+impl TryGetable for Upid {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        Uuid::try_get_by(res, index).map(Upid::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sea_orm_cycle() {
+        let want = Upid::new("user");
+        let value: Value = want.into();
+        let got: Upid = value.unwrap();
+
+        assert_eq!(got, want)
+    }
+
+    #[test]
+    fn sea_orm_null() {
+        assert!(<Upid as ValueType>::try_from(Upid::null()).is_err());
+        assert!(<Upid as ValueType>::try_from(Value::Int(Some(1))).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Serde helpers to choose the representation of each Upid field.
+//!
+//! Each module works with `#[serde(with = "...")]`, and has an `option`
+//! submodule for `Option<Upid>` fields, with `None` as null.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use upid::Upid;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(with = "upid::serde::as_string")]
+//!     id: Upid,
+//!     #[serde(with = "upid::serde::as_bytes::option")]
+//!     parent: Option<Upid>,
+//! }
+//! ```
+
+use crate::DecodeError;
+
+/// Adds an `option` module built on the parent's `serialize` and `deserialize`
+macro_rules! option_module {
+    () => {
+        /// The same representation for `Option<Upid>`, with `None` as null
+        pub mod option {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            use crate::Upid;
+
+            struct Wrapper(Upid);
+
+            impl Serialize for Wrapper {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    super::serialize(&self.0, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for Wrapper {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    super::deserialize(deserializer).map(Wrapper)
+                }
+            }
+
+            /// Serializes an optional Upid
+            pub fn serialize<S: Serializer>(
+                upid: &Option<Upid>,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                upid.map(Wrapper).serialize(serializer)
+            }
+
+            /// Deserializes an optional Upid
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Option<Upid>, D::Error> {
+                Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|wrapper| wrapper.0))
+            }
+        }
+    };
+}
+
+/// Serializes as `{"code": ..., "message": ...}`, from [`DecodeError::code`] and `Display`
+impl serde::Serialize for DecodeError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("DecodeError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Serde helpers to store a Upid as its 27-character string
+pub mod as_string {
+    use std::fmt;
+
+    use serde::de::{Error, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use crate::Upid;
+
+    /// Serializes a Upid as a string
+    pub fn serialize<S: Serializer>(upid: &Upid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(upid)
+    }
+
+    /// Deserializes a Upid from a string
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Upid, D::Error> {
+        deserializer.deserialize_str(StrVisitor)
+    }
+
+    struct StrVisitor;
+
+    impl Visitor<'_> for StrVisitor {
+        type Value = Upid;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a Upid string")
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Upid, E> {
+            Upid::from_string(v).map_err(E::custom)
+        }
+    }
+
+    option_module!();
+}
+
+/// Serde helpers to store a Upid as a u128
+///
+/// Not every format supports 128-bit integers.
+pub mod as_u128 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Upid;
+
+    /// Serializes a Upid as a u128
+    pub fn serialize<S: Serializer>(upid: &Upid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u128(upid.0)
+    }
+
+    /// Deserializes a Upid from a u128
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Upid, D::Error> {
+        u128::deserialize(deserializer).map(Upid)
+    }
+
+    option_module!();
+}
+
+/// Serde helpers to store a Upid as 16 big-endian bytes
+///
+/// Formats without a bytes type, like JSON, will store an array of numbers.
+pub mod as_bytes {
+    use std::fmt;
+
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use crate::Upid;
+
+    /// Serializes a Upid as bytes
+    pub fn serialize<S: Serializer>(upid: &Upid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&upid.to_bytes())
+    }
+
+    /// Deserializes a Upid from bytes, or a sequence of 16 bytes
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Upid, D::Error> {
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Upid;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("16 bytes")
+        }
+
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Upid, E> {
+            Upid::from_slice(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Upid, A::Error> {
+            let mut bytes = [0; 16];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(A::Error::invalid_length(17, &self));
+            }
+            Ok(Upid::from_bytes(bytes))
+        }
+    }
+
+    option_module!();
+}
+
+/// Serde helpers for migrating from UUIDs, accepting either a Upid or a UUID string
+///
+/// A hyphenated UUID is read by reinterpreting its bits, the same as `From<Uuid>`
+/// with the `uuid` feature. Most UUIDs don't map to a valid Upid string, so those
+/// are written back as UUIDs, and everything else as the usual Upid string.
+pub mod compat {
+    use std::fmt;
+
+    use serde::de::{Error, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use crate::{DecodeError, Upid};
+
+    /// Serializes a Upid as a string, or a hyphenated UUID if it isn't a valid Upid
+    pub fn serialize<S: Serializer>(upid: &Upid, serializer: S) -> Result<S::Ok, S::Error> {
+        match Upid::from_string(&upid.to_string()) {
+            Ok(_) => serializer.collect_str(upid),
+            Err(_) => {
+                let hex = upid.to_hex();
+                serializer.collect_str(&format_args!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                ))
+            }
+        }
+    }
+
+    /// Deserializes a Upid from a Upid string or a hyphenated UUID string
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Upid, D::Error> {
+        deserializer.deserialize_str(CompatVisitor)
+    }
+
+    /// Parses a hyphenated UUID, eg `01909bc6-0f93-7043-5c61-c99524d61576`
+    fn from_uuid_str(text: &str) -> Result<Upid, DecodeError> {
+        let bytes = text.as_bytes();
+        if [8, 13, 18, 23].iter().any(|&i| bytes[i] != b'-') {
+            return Err(DecodeError::InvalidChar);
+        }
+        Upid::from_hex(&text.replace('-', ""))
+    }
+
+    struct CompatVisitor;
+
+    impl Visitor<'_> for CompatVisitor {
+        type Value = Upid;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a Upid or UUID string")
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Upid, E> {
+            match v.len() {
+                36 => from_uuid_str(v),
+                _ => Upid::from_string(v),
+            }
+            .map_err(E::custom)
+        }
+    }
+
+    option_module!();
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use crate::Upid;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "super::as_string")]
+        text: Upid,
+        #[serde(with = "super::as_u128")]
+        number: Upid,
+        #[serde(with = "super::as_bytes")]
+        bytes: Upid,
+        #[serde(with = "super::as_string::option")]
+        maybe_text: Option<Upid>,
+        #[serde(with = "super::as_bytes::option")]
+        maybe_bytes: Option<Upid>,
+    }
+
+    #[test]
+    fn serde_cycle() {
+        let upid = Upid::from_string(TEXT).unwrap();
+        let event = Event {
+            text: upid,
+            number: upid,
+            bytes: upid,
+            maybe_text: Some(upid),
+            maybe_bytes: None,
+        };
+
+        let text = serde_json::to_string(&event).unwrap();
+        assert!(text.contains(&format!(r#""number":{}"#, u128::from(upid))));
+
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["text"], TEXT);
+        assert_eq!(json["bytes"], serde_json::json!(upid.to_bytes()));
+        assert_eq!(json["maybe_text"], TEXT);
+        assert!(json["maybe_bytes"].is_null());
+
+        assert_eq!(serde_json::from_str::<Event>(&text).unwrap(), event);
+    }
+
+    #[test]
+    fn compat() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Record {
+            #[serde(with = "super::compat")]
+            id: Upid,
+            #[serde(with = "super::compat::option")]
+            parent: Option<Upid>,
+        }
+
+        let upid = Upid::from_string(TEXT).unwrap();
+        let from_upid: Record = serde_json::from_str(&format!(
+            r#"{{"id": "{TEXT}", "parent": "01909bc6-0f93-7043-5c61-c99524d61576"}}"#
+        ))
+        .unwrap();
+        assert_eq!(from_upid.id, upid);
+        assert_eq!(from_upid.parent, Some(upid));
+
+        // a random v4 UUID isn't a valid Upid, so it's written back as it came
+        let uuid = "9b2f6d4e-1c3a-4f5b-8e7d-0a1b2c3d4e5f";
+        let json = format!(r#"{{"id":"{uuid}","parent":null}}"#);
+        let record: Record = serde_json::from_str(&json).unwrap();
+        assert_eq!(record.id.to_hex(), uuid.replace('-', ""));
+        assert_eq!(serde_json::to_string(&record).unwrap(), json);
+
+        let json = serde_json::to_string(&from_upid).unwrap();
+        assert_eq!(json, format!(r#"{{"id":"{TEXT}","parent":"{TEXT}"}}"#));
+
+        let bad = r#"{"id":"9b2f6d4e+1c3a-4f5b-8e7d-0a1b2c3d4e5f","parent":null}"#;
+        assert!(serde_json::from_str::<Record>(bad).is_err());
+    }
+
+    #[test]
+    fn serde_invalid() {
+        #[derive(Debug, Deserialize)]
+        struct Text(
+            #[serde(with = "super::as_string")]
+            #[allow(dead_code)]
+            Upid,
+        );
+        #[derive(Debug, Deserialize)]
+        struct Bytes(
+            #[serde(with = "super::as_bytes")]
+            #[allow(dead_code)]
+            Upid,
+        );
+
+        let err = serde_json::from_str::<Text>(r#""user_short""#).unwrap_err();
+        assert!(err.to_string().contains("invalid length"));
+        assert!(serde_json::from_str::<Bytes>("[1, 2, 3]").is_err());
+        assert!(serde_json::from_str::<Bytes>(&format!("{:?}", [0u8; 17])).is_err());
+    }
+
+    #[test]
+    fn serialize_error() {
+        let err = Upid::from_string("user_2acdrlkjmhs6ar53taem6z").unwrap_err();
+        let json = serde_json::to_string(&err).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"code":"unsupported_version","message":"unsupported version 'z'"}"#
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Shorter Upids for human-facing codes.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::b32::{gather, spread, DecodeError, DECODE, ENCODE};
+use crate::{rng, Clock, SystemClock};
+
+const PREFIX_CHAR_LEN: usize = 4;
+const TIME_CHAR_LEN: usize = 8;
+const TIME_MASK: u128 = (1 << 40) - 1;
+
+/// A Upid with only `R` random characters, for order references and the like
+///
+/// The string is a four-character prefix, `_`, eight timestamp characters and then
+/// `R` random characters, so `13 + R` characters in total: 19 with the default of 6.
+/// It uses the same alphabet as [`Upid`](crate::Upid), sorts by time in the same way,
+/// and the prefix is padded or clipped to four characters in the same way.
+///
+/// Each random character carries 5 bits, and only IDs with the same prefix
+/// created in the same 256ms can collide. With `n` of those, the chance of a collision
+/// is roughly `n² / 2^(5R + 1)`: for the default `R = 6` that is 1% at around 4,600 IDs
+/// per 256ms, and for `R = 4` it is 1% at around 145. `R` must be from 1 to 12.
+///
+/// # Example
+/// ```rust
+/// use upid::ShortUpid;
+///
+/// let code = ShortUpid::<6>::new("ordr");
+/// assert_eq!(code.to_string().len(), 19);
+/// assert_eq!(code.prefix(), "ordr");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortUpid<const R: usize = 6>(u128);
+
+impl<const R: usize> ShortUpid<R> {
+    /// Length of the string form, including the `_`
+    pub const STR_LEN: usize = PREFIX_CHAR_LEN + 1 + TIME_CHAR_LEN + R;
+
+    const RANDOM_BITS: usize = {
+        assert!(
+            R >= 1 && R <= 12,
+            "ShortUpid needs 1 to 12 random characters"
+        );
+        5 * R
+    };
+    const TIME_SHIFT: usize = Self::RANDOM_BITS + 5 * PREFIX_CHAR_LEN;
+
+    /// Creates a new ShortUpid with the given prefix and the current time (UTC)
+    pub fn new(prefix: &str) -> ShortUpid<R> {
+        ShortUpid::from_prefix_and_datetime(prefix, SystemClock.now())
+    }
+
+    /// Creates a new ShortUpid with the given prefix and datetime
+    pub fn from_prefix_and_datetime(prefix: &str, datetime: SystemTime) -> ShortUpid<R> {
+        let milliseconds = datetime
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_millis();
+        ShortUpid::from_prefix_and_milliseconds(prefix, milliseconds)
+    }
+
+    /// Creates a new ShortUpid with the given prefix and timestamp in milliseconds
+    pub fn from_prefix_and_milliseconds(prefix: &str, milliseconds: u128) -> ShortUpid<R> {
+        let time_bits = (milliseconds >> 8) & TIME_MASK;
+        let random = rng::random_u64() as u128 & ((1 << Self::RANDOM_BITS) - 1);
+
+        let mut prefix_bits = 0;
+        let mut chars = prefix.chars();
+        for _ in 0..PREFIX_CHAR_LEN {
+            let value = chars.next().map_or(31, |c| {
+                u8::try_from(c).map_or(31, |b| DECODE[b as usize].min(31))
+            });
+            prefix_bits = (prefix_bits << 5) | value as u128;
+        }
+
+        ShortUpid((time_bits << Self::TIME_SHIFT) | (random << (5 * PREFIX_CHAR_LEN)) | prefix_bits)
+    }
+
+    /// Creates a ShortUpid from its string form
+    pub fn from_string(encoded: &str) -> Result<ShortUpid<R>, DecodeError> {
+        let bytes = encoded.as_bytes();
+        if bytes.len() != Self::STR_LEN || bytes[PREFIX_CHAR_LEN] != b'_' {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let mut values = [0u8; PREFIX_CHAR_LEN + TIME_CHAR_LEN + 12];
+        let chars = bytes[..PREFIX_CHAR_LEN]
+            .iter()
+            .chain(&bytes[PREFIX_CHAR_LEN + 1..]);
+        for (v, &b) in values.iter_mut().zip(chars) {
+            *v = DECODE[b as usize];
+            if *v > 31 {
+                return Err(DecodeError::InvalidChar);
+            }
+        }
+
+        let prefix = gather(&values[..PREFIX_CHAR_LEN]);
+        let time = gather(&values[PREFIX_CHAR_LEN..PREFIX_CHAR_LEN + TIME_CHAR_LEN]);
+        let random = gather(&values[PREFIX_CHAR_LEN + TIME_CHAR_LEN..][..R]);
+
+        Ok(ShortUpid(
+            (time << Self::TIME_SHIFT) | (random << (5 * PREFIX_CHAR_LEN)) | prefix,
+        ))
+    }
+
+    /// Gets the prefix of this ShortUpid
+    pub fn prefix(&self) -> String {
+        let mut values = [0u8; PREFIX_CHAR_LEN];
+        spread(self.0, &mut values);
+        values.iter().map(|&v| ENCODE[v as usize] as char).collect()
+    }
+
+    /// Gets the timestamp in milliseconds, with a precision of 256ms
+    pub fn milliseconds(&self) -> u64 {
+        ((self.0 >> Self::TIME_SHIFT) << 8) as u64
+    }
+
+    /// Gets the datetime of this ShortUpid, with a precision of 256ms
+    pub fn datetime(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(self.milliseconds())
+    }
+}
+
+impl<const R: usize> fmt::Display for ShortUpid<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut values = [0u8; PREFIX_CHAR_LEN + TIME_CHAR_LEN + 12];
+        let values = &mut values[..PREFIX_CHAR_LEN + TIME_CHAR_LEN + R];
+        spread(
+            self.0 >> (5 * PREFIX_CHAR_LEN),
+            &mut values[PREFIX_CHAR_LEN..],
+        );
+        spread(self.0, &mut values[..PREFIX_CHAR_LEN]);
+
+        let mut buffer = [0u8; PREFIX_CHAR_LEN + 1 + TIME_CHAR_LEN + 12];
+        let buffer = &mut buffer[..Self::STR_LEN];
+        for (i, &v) in values.iter().enumerate() {
+            let i = if i < PREFIX_CHAR_LEN { i } else { i + 1 };
+            buffer[i] = ENCODE[v as usize];
+        }
+        buffer[PREFIX_CHAR_LEN] = b'_';
+
+        // the buffer only holds characters from the alphabet and '_'
+        f.pad(core::str::from_utf8(buffer).expect("ShortUpid strings are ASCII"))
+    }
+}
+
+impl<const R: usize> FromStr for ShortUpid<R> {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ShortUpid::from_string(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_cycle() {
+        let code = ShortUpid::<6>::from_prefix_and_milliseconds("ordr", 1720600366848);
+        let text = code.to_string();
+
+        assert_eq!(text.len(), 19);
+        assert!(text.starts_with("ordr_2acdrlkj"));
+        assert_eq!(code.milliseconds(), 1720600366848);
+        assert_eq!(ShortUpid::<6>::from_string(&text), Ok(code));
+    }
+
+    #[test]
+    fn short_lengths() {
+        let one = ShortUpid::<1>::new("a");
+        assert_eq!(one.to_string().len(), 14);
+        assert_eq!(one.prefix(), "azzz");
+        assert_eq!(one.to_string().parse(), Ok(one));
+
+        let twelve = ShortUpid::<12>::new("toolong");
+        assert_eq!(twelve.to_string().len(), 25);
+        assert_eq!(twelve.prefix(), "tool");
+        assert_eq!(twelve.to_string().parse(), Ok(twelve));
+    }
+
+    #[test]
+    fn short_errors() {
+        assert!(ShortUpid::<6>::from_string("ordr_2acdrlkj234567").is_ok());
+        assert_eq!(
+            ShortUpid::<6>::from_string("ordr_2acdrlkj23456"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            ShortUpid::<6>::from_string("ordr2_acdrlkj234567"),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            ShortUpid::<6>::from_string("ordr_2acdrlkj23456!"),
+            Err(DecodeError::InvalidChar)
+        );
+    }
+
+    #[test]
+    fn short_sorts_by_time() {
+        let early = ShortUpid::<6>::from_prefix_and_milliseconds("zzzz", 1_000_000);
+        let late = ShortUpid::<6>::from_prefix_and_milliseconds("aaaa", 2_000_000);
+        assert!(early < late);
+
+        // strings sort by time within a prefix
+        let late = ShortUpid::<6>::from_prefix_and_milliseconds("zzzz", 2_000_000);
+        assert!(early.to_string() < late.to_string());
+    }
+}
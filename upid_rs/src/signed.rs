@@ -0,0 +1,141 @@
+//! Upids carrying an HMAC tag, so forged IDs can be rejected without a lookup.
+
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{DecodeError, Upid};
+
+/// The tag takes the 32 random bits just below the timestamp
+const TAG_SHIFT: u32 = 56;
+const TAG_MASK: u128 = 0xffff_ffff << TAG_SHIFT;
+
+/// A Upid whose top 32 random bits are an HMAC-SHA256 tag over the rest of it
+///
+/// The tag covers the timestamp, prefix, version and remaining random bits, under a
+/// server secret. A service holding the secret can cheaply reject made-up or altered
+/// IDs with [`SignedUpid::verify`] before they reach the database.
+///
+/// With a 32-bit tag, each guess at a forgery has a 1 in 2^32 chance of passing,
+/// so this is a filter against junk and enumeration rather than an authentication scheme.
+/// Signed Upids have 32 fewer random bits, so collide sooner than plain ones.
+///
+/// # Example
+/// ```rust
+/// use upid::{SignedUpid, Upid};
+///
+/// let key = b"server secret";
+/// let id = SignedUpid::new("user", key);
+/// assert!(id.verify(key));
+///
+/// let forged = SignedUpid::from_upid(Upid::new("user"));
+/// assert!(!forged.verify(key));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignedUpid(Upid);
+
+impl SignedUpid {
+    /// Creates a new Upid with the current time (UTC), tagged with `key`
+    pub fn new(prefix: &str, key: &[u8]) -> SignedUpid {
+        SignedUpid::sign(Upid::from_prefix(prefix), key)
+    }
+
+    /// Replaces the tag bits of `upid` with a tag from `key`
+    pub fn sign(upid: Upid, key: &[u8]) -> SignedUpid {
+        let untagged = upid.0 & !TAG_MASK;
+        let tag = u32::from_be_bytes(tag(untagged, key)) as u128;
+        SignedUpid(Upid(untagged | (tag << TAG_SHIFT)))
+    }
+
+    /// Wraps a Upid without checking its tag, see [`SignedUpid::verify`]
+    pub fn from_upid(upid: Upid) -> SignedUpid {
+        SignedUpid(upid)
+    }
+
+    /// Creates a SignedUpid from a Base32 encoded string, without checking its tag
+    pub fn from_string(encoded: &str) -> Result<SignedUpid, DecodeError> {
+        Ok(SignedUpid(Upid::from_string(encoded)?))
+    }
+
+    /// Checks the tag against `key`, in constant time
+    pub fn verify(&self, key: &[u8]) -> bool {
+        let tag = ((self.0 .0 & TAG_MASK) >> TAG_SHIFT) as u32;
+        mac(self.0 .0 & !TAG_MASK, key)
+            .verify_truncated_left(&tag.to_be_bytes())
+            .is_ok()
+    }
+
+    /// Gets the underlying Upid
+    pub fn upid(&self) -> Upid {
+        self.0
+    }
+}
+
+fn mac(untagged: u128, key: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&untagged.to_be_bytes());
+    mac
+}
+
+fn tag(untagged: u128, key: &[u8]) -> [u8; 4] {
+    let digest = mac(untagged, key).finalize().into_bytes();
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+impl From<SignedUpid> for Upid {
+    fn from(signed: SignedUpid) -> Self {
+        signed.0
+    }
+}
+
+impl fmt::Display for SignedUpid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for SignedUpid {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SignedUpid::from_string(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &[u8] = b"server secret";
+
+    #[test]
+    fn signed_verify() {
+        let id = SignedUpid::new("user", KEY);
+        assert!(id.verify(KEY));
+        assert!(!id.verify(b"other secret"));
+        assert_eq!(id.upid().prefix(), "user");
+
+        let v2 = SignedUpid::sign(Upid::new_v2("tx"), KEY);
+        assert!(v2.verify(KEY));
+        assert_eq!(v2.upid().prefix(), "tx");
+    }
+
+    #[test]
+    fn signed_rejects_tampering() {
+        let id = SignedUpid::new("user", KEY);
+        for bit in 0..128 {
+            let tampered = SignedUpid::from_upid(Upid(id.0 .0 ^ (1 << bit)));
+            assert!(!tampered.verify(KEY), "bit {bit}");
+        }
+    }
+
+    #[test]
+    fn signed_string_cycle() {
+        let id = SignedUpid::new("user", KEY);
+        let parsed: SignedUpid = id.to_string().parse().unwrap();
+        assert_eq!(parsed, id);
+        assert!(parsed.verify(KEY));
+    }
+}
@@ -0,0 +1,66 @@
+//! Convert between Upid and MySQL values for `sqlx`.
+//!
+//! A Upid is bound as the 16 big-endian bytes of the Upid, for a `BINARY(16)` column,
+//! which sorts by time like the Upid itself. It can also be read from a text column
+//! holding the string form, so existing `CHAR(27)` columns keep working.
+
+use sqlx::decode::Decode;
+use sqlx::encode::{Encode, IsNull};
+use sqlx::error::BoxDynError;
+use sqlx::mysql::{MySql, MySqlTypeInfo, MySqlValueRef};
+use sqlx::types::Type;
+
+use crate::Upid;
+
+impl Type<MySql> for Upid {
+    fn type_info() -> MySqlTypeInfo {
+        <[u8] as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <[u8] as Type<MySql>>::compatible(ty) || <str as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, MySql> for Upid {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<IsNull, BoxDynError> {
+        <&[u8] as Encode<MySql>>::encode(&self.to_bytes()[..], buf)
+    }
+}
+
+impl Decode<'_, MySql> for Upid {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Upid, BoxDynError> {
+        let raw = <&[u8] as Decode<MySql>>::decode(value)?;
+        // a valid string is never 16 bytes long, so the two forms can't be confused
+        match <[u8; 16]>::try_from(raw) {
+            Ok(bytes) => Ok(Upid::from_bytes(bytes)),
+            Err(_) => Ok(Upid::from_string(std::str::from_utf8(raw)?)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_binary() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let mut buf = Vec::new();
+        let is_null = Encode::<MySql>::encode_by_ref(&upid, &mut buf).unwrap();
+
+        assert!(matches!(is_null, IsNull::No));
+        // length-encoded bytes
+        assert_eq!(buf[0], 16);
+        assert_eq!(buf[1..], upid.to_bytes());
+    }
+
+    #[test]
+    fn type_info() {
+        let binary = <Vec<u8> as Type<MySql>>::type_info();
+        let text = <String as Type<MySql>>::type_info();
+
+        assert!(<Upid as Type<MySql>>::compatible(&binary));
+        assert!(<Upid as Type<MySql>>::compatible(&text));
+    }
+}
@@ -0,0 +1,101 @@
+//! An endless iterator of Upids, for feeding ID generation into iterator pipelines.
+
+use std::iter::FusedIterator;
+
+use crate::{PrefixedGenerator, Upid};
+
+/// Mask of the prefix and version bits in the binary
+const PREFIX_MASK: u128 = 0xff_ffff;
+
+/// An infinite iterator of new Upids with the same prefix
+///
+/// # Example
+/// ```rust
+/// use upid::UpidStream;
+///
+/// let names = ["ada", "grace", "edsger"];
+/// let users: Vec<_> = UpidStream::new("user").zip(names).collect();
+///
+/// assert_eq!(users.len(), 3);
+/// assert_eq!(users[0].0.prefix(), "user");
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpidStream {
+    generator: PrefixedGenerator,
+    last: Option<u128>,
+}
+
+impl UpidStream {
+    /// Creates a stream of Upids with the given prefix and the current time
+    pub fn new(prefix: &str) -> UpidStream {
+        UpidStream {
+            generator: PrefixedGenerator::new(prefix),
+            last: None,
+        }
+    }
+
+    /// Creates a stream where every Upid is strictly greater than the one before
+    ///
+    /// As with `Upid::new_monotonic`, if the clock hasn't moved on the random bits
+    /// of the last Upid are incremented instead of drawing new ones. Unlike it,
+    /// this only orders the Upids from this stream, so needs no global state.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::UpidStream;
+    ///
+    /// let upids: Vec<_> = UpidStream::monotonic("user").take(1000).collect();
+    /// assert!(upids.windows(2).all(|w| w[0] < w[1]));
+    /// ```
+    pub fn monotonic(prefix: &str) -> UpidStream {
+        UpidStream {
+            generator: PrefixedGenerator::new(prefix),
+            last: Some(0),
+        }
+    }
+}
+
+impl Iterator for UpidStream {
+    type Item = Upid;
+
+    fn next(&mut self) -> Option<Upid> {
+        let candidate = self.generator.generate();
+        let Some(last) = self.last else {
+            return Some(candidate);
+        };
+        let next = if candidate.0 > last {
+            candidate.0
+        } else {
+            // bump everything above the prefix, and keep the prefix
+            (((last >> 24) + 1) << 24) | (candidate.0 & PREFIX_MASK)
+        };
+        self.last = Some(next);
+        Some(Upid(next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl FusedIterator for UpidStream {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stream_is_endless() {
+        let upids: Vec<_> = UpidStream::new("user").take(100).collect();
+        assert_eq!(upids.len(), 100);
+        assert!(upids.iter().all(|upid| upid.prefix() == "user"));
+        assert_ne!(upids[0], upids[1]);
+    }
+
+    #[test]
+    fn stream_monotonic() {
+        let upids: Vec<_> = UpidStream::monotonic("user").take(10_000).collect();
+        assert!(upids.windows(2).all(|w| w[0] < w[1]));
+        assert!(upids.iter().all(|upid| upid.prefix() == "user"));
+    }
+}
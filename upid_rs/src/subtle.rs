@@ -0,0 +1,46 @@
+//! Constant-time comparison of Upids, for when they're used as capability tokens.
+//!
+//! The ordinary `PartialEq` can return as soon as it finds a differing byte,
+//! which leaks how much of a guess was right through its timing.
+
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::Upid;
+
+impl ConstantTimeEq for Upid {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
+}
+
+impl Upid {
+    /// Compares two Upids in constant time
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let token = Upid::new("tokn");
+    /// assert!(token.ct_eq(&token));
+    /// assert!(!token.ct_eq(&Upid::new("tokn")));
+    /// ```
+    pub fn ct_eq(&self, other: &Upid) -> bool {
+        ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ct_eq_matches_eq() {
+        let a = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        let b = Upid::from_string("user_2acdrlkjmhs6ar53taem7a").unwrap();
+
+        assert!(a.ct_eq(&a));
+        assert!(!a.ct_eq(&b));
+        assert_eq!(a.ct_eq(&b), a == b);
+        assert!(bool::from(ConstantTimeEq::ct_eq(&b, &b)));
+    }
+}
@@ -0,0 +1,147 @@
+//! Deterministic Upids for snapshot tests.
+//!
+//! [`fixed`] builds a single Upid from a time and a seed, and [`freeze`] makes
+//! [`Upid::new`] and friends deterministic on the current thread until the guard
+//! is dropped. Each test runs on its own thread, so frozen tests don't affect
+//! each other or tests that run alongside them.
+
+use std::cell::Cell;
+use std::time::{Duration, SystemTime};
+
+use crate::Upid;
+
+#[derive(Clone, Copy)]
+struct Frozen {
+    now: SystemTime,
+    state: u64,
+}
+
+thread_local! {
+    static FROZEN: Cell<Option<Frozen>> = const { Cell::new(None) };
+}
+
+/// Creates the Upid that `Upid::from_prefix(prefix)` would give first under `freeze(millis, seed)`
+///
+/// # Example
+/// ```rust
+/// use upid::testing;
+///
+/// let upid = testing::fixed("user", 1720568902000, 42);
+/// assert_eq!(upid, testing::fixed("user", 1720568902000, 42));
+/// assert_eq!(upid.milliseconds(), 1720568901888);
+/// ```
+pub fn fixed(prefix: &str, millis: u64, seed: u64) -> Upid {
+    let _guard = freeze(millis, seed);
+    Upid::from_prefix(prefix)
+}
+
+/// Stops the clock at `millis` and seeds the random bits, until the guard is dropped
+///
+/// Successive Upids still differ, but the sequence is the same on every run.
+/// Guards can be nested, and dropping one restores whatever was in place before it.
+///
+/// # Example
+/// ```rust
+/// use upid::{testing, Upid};
+///
+/// let first = {
+///     let _guard = testing::freeze(1720568902000, 42);
+///     (Upid::new("user"), Upid::new("user"))
+/// };
+/// let second = {
+///     let _guard = testing::freeze(1720568902000, 42);
+///     (Upid::new("user"), Upid::new("user"))
+/// };
+///
+/// assert_eq!(first, second);
+/// assert_ne!(first.0, first.1);
+/// ```
+pub fn freeze(millis: u64, seed: u64) -> FreezeGuard {
+    let frozen = Frozen {
+        now: SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+        state: seed,
+    };
+    FreezeGuard {
+        previous: FROZEN.with(|cell| cell.replace(Some(frozen))),
+    }
+}
+
+/// Keeps Upid generation frozen on the current thread until dropped, see [`freeze`]
+#[must_use = "generation is only frozen until the guard is dropped"]
+pub struct FreezeGuard {
+    previous: Option<Frozen>,
+}
+
+impl Drop for FreezeGuard {
+    fn drop(&mut self) {
+        FROZEN.with(|cell| cell.set(self.previous));
+    }
+}
+
+impl std::fmt::Debug for FreezeGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FreezeGuard").finish_non_exhaustive()
+    }
+}
+
+/// Gets the frozen time, if any
+pub(crate) fn frozen_now() -> Option<SystemTime> {
+    FROZEN.with(|cell| cell.get().map(|frozen| frozen.now))
+}
+
+/// Steps the seeded generator (splitmix64), if any
+pub(crate) fn frozen_random_u64() -> Option<u64> {
+    FROZEN.with(|cell| {
+        let mut frozen = cell.get()?;
+        frozen.state = frozen.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        cell.set(Some(frozen));
+
+        let mut z = frozen.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Some(z ^ (z >> 31))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_is_stable() {
+        let upid = fixed("user", 1720600366848, 7);
+        assert_eq!(upid, fixed("user", 1720600366848, 7));
+        assert_ne!(upid, fixed("user", 1720600366848, 8));
+        assert_eq!(upid.milliseconds(), 1720600366848);
+        assert_eq!(upid.prefix(), "user");
+    }
+
+    #[test]
+    fn freeze_is_scoped() {
+        let outer = freeze(1720600366848, 7);
+        let first = Upid::new("user");
+        assert_eq!(first, fixed("user", 1720600366848, 7));
+        {
+            let _inner = freeze(0, 1);
+            assert_eq!(Upid::new("user").milliseconds(), 0);
+        }
+        // the outer sequence carries on where it was
+        let second = Upid::new("user");
+        assert_eq!(second.milliseconds(), 1720600366848);
+        assert_ne!(first, second);
+
+        drop(outer);
+        assert!(frozen_now().is_none());
+        assert!(Upid::new("user").milliseconds() > 1720600366848);
+    }
+
+    #[test]
+    fn freeze_covers_v2() {
+        let _guard = freeze(1720600366848, 7);
+        let upid = Upid::new_v2("tx");
+        assert_eq!(upid.milliseconds(), 1720600366848);
+
+        let _guard = freeze(1720600366848, 7);
+        assert_eq!(Upid::new_v2("tx"), upid);
+    }
+}
@@ -0,0 +1,119 @@
+//! Chronological ordering of Upids with mixed prefixes.
+//!
+//! The string form sorts by prefix first, so `"acct_..."` comes before `"user_..."`
+//! whatever their times, while the binary form (and `Upid`'s own `Ord`) sorts by time
+//! only within one version, as version `c` counts from a later epoch.
+//! These helpers make the chronological order explicit across prefixes and versions,
+//! and break ties within the same 256ms the same way the strings do.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{b32, Upid};
+
+impl Upid {
+    /// Compares the timestamps of two Upids, ignoring everything else
+    ///
+    /// Upids created within the same 256ms compare as equal.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::cmp::Ordering;
+    /// use upid::Upid;
+    ///
+    /// let early = Upid::from_prefix_and_milliseconds("user", 1720568902000);
+    /// let late = Upid::from_prefix_and_milliseconds("acct", 1720568903000);
+    ///
+    /// assert_eq!(early.cmp_by_time(&late), Ordering::Less);
+    /// assert!(early.to_string() > late.to_string());
+    /// ```
+    pub fn cmp_by_time(&self, other: &Upid) -> Ordering {
+        self.milliseconds().cmp(&other.milliseconds())
+    }
+}
+
+/// A Upid that sorts by time, then by its string form
+///
+/// # Example
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use upid::{TimeOrdered, Upid};
+///
+/// let early = Upid::from_prefix_and_milliseconds("user", 1720568902000);
+/// let late = Upid::from_prefix_and_milliseconds("acct", 1720568903000);
+///
+/// let set: BTreeSet<_> = [late, early].into_iter().map(TimeOrdered).collect();
+/// let ordered: Vec<Upid> = set.into_iter().map(|t| t.0).collect();
+/// assert_eq!(ordered, [early, late]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeOrdered(pub Upid);
+
+impl PartialOrd for TimeOrdered {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimeOrdered {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_by_time(&other.0).then_with(|| {
+            let (mut a, mut b) = ([0; b32::STR_LEN], [0; b32::STR_LEN]);
+            b32::encode_into(self.0 .0, &mut a);
+            b32::encode_into(other.0 .0, &mut b);
+            a.cmp(&b)
+        })
+    }
+}
+
+impl From<Upid> for TimeOrdered {
+    fn from(upid: Upid) -> Self {
+        TimeOrdered(upid)
+    }
+}
+
+impl From<TimeOrdered> for Upid {
+    fn from(ordered: TimeOrdered) -> Self {
+        ordered.0
+    }
+}
+
+impl fmt::Display for TimeOrdered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn time_then_string() {
+        let ms = 1720600366848;
+        let a = Upid::from_prefix_and_milliseconds("user", ms);
+        let b = Upid::from_prefix_and_milliseconds("acct", ms);
+        let c = Upid::from_prefix_and_milliseconds("acct", ms + 256);
+        let d = Upid::from_prefix_and_milliseconds_v2("ab", ms + 256);
+
+        assert_eq!(a.cmp_by_time(&b), Ordering::Equal);
+        assert_eq!(b.cmp_by_time(&c), Ordering::Less);
+
+        let mut sorted: Vec<_> = [d, c, a, b].into_iter().map(TimeOrdered).collect();
+        sorted.sort();
+        let sorted: Vec<Upid> = sorted.into_iter().map(Upid::from).collect();
+        assert_eq!(sorted, [b, a, d, c]);
+    }
+
+    #[test]
+    fn mixed_versions() {
+        let ms = 1720600366848;
+        let v1 = Upid::from_prefix_and_milliseconds("user", ms);
+        let v3 = Upid::from_prefix_and_milliseconds_v3("user", ms + 256);
+
+        // the raw bits put version 'c' decades earlier
+        assert!(v3 < v1);
+        assert_eq!(v1.cmp_by_time(&v3), Ordering::Less);
+        assert!(TimeOrdered(v1) < TimeOrdered(v3));
+    }
+}
@@ -0,0 +1,182 @@
+//! Upids whose prefix is checked against a type.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::{DecodeError, Upid};
+
+/// A prefix known at compile time, for use with [`TypedUpid`]
+///
+/// # Example
+/// ```rust
+/// use upid::UpidPrefix;
+///
+/// struct User;
+///
+/// impl UpidPrefix for User {
+///     const PREFIX: &'static str = "user";
+/// }
+/// ```
+pub trait UpidPrefix {
+    /// The four-character prefix that every Upid of this type must have
+    const PREFIX: &'static str;
+}
+
+/// A Upid that is known to have the prefix `P::PREFIX`
+///
+/// Parsing fails with [`DecodeError::UnexpectedPrefix`] if the prefix is wrong,
+/// which stops an order ID from being passed where a user ID was expected.
+///
+/// # Example
+/// ```rust
+/// use upid::{DecodeError, TypedUpid, UpidPrefix};
+///
+/// struct User;
+///
+/// impl UpidPrefix for User {
+///     const PREFIX: &'static str = "user";
+/// }
+///
+/// let id = TypedUpid::<User>::new();
+/// assert_eq!(id.upid().prefix(), "user");
+///
+/// let wrong = TypedUpid::<User>::from_string("ordr_2accvpp5guht4dts56je5a");
+/// assert_eq!(wrong, Err(DecodeError::UnexpectedPrefix));
+/// ```
+pub struct TypedUpid<P: UpidPrefix> {
+    upid: Upid,
+    prefix: PhantomData<fn() -> P>,
+}
+
+impl<P: UpidPrefix> TypedUpid<P> {
+    /// Creates a new Upid with the prefix `P::PREFIX` and the current time (UTC)
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> TypedUpid<P> {
+        TypedUpid {
+            upid: Upid::from_prefix(P::PREFIX),
+            prefix: PhantomData,
+        }
+    }
+
+    /// Checks that `upid` has the prefix `P::PREFIX`
+    pub fn from_upid(upid: Upid) -> Result<TypedUpid<P>, DecodeError> {
+        if upid.prefix() != P::PREFIX {
+            return Err(DecodeError::UnexpectedPrefix);
+        }
+        Ok(TypedUpid {
+            upid,
+            prefix: PhantomData,
+        })
+    }
+
+    /// Creates a Upid from a Base32 encoded string, checking its prefix
+    pub fn from_string(encoded: &str) -> Result<TypedUpid<P>, DecodeError> {
+        TypedUpid::from_upid(Upid::from_string(encoded)?)
+    }
+
+    /// Gets the untyped Upid
+    pub fn upid(&self) -> Upid {
+        self.upid
+    }
+}
+
+// These are implemented by hand so they don't require `P` to implement them
+
+impl<P: UpidPrefix> Clone for TypedUpid<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: UpidPrefix> Copy for TypedUpid<P> {}
+
+impl<P: UpidPrefix> PartialEq for TypedUpid<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.upid == other.upid
+    }
+}
+
+impl<P: UpidPrefix> Eq for TypedUpid<P> {}
+
+impl<P: UpidPrefix> PartialOrd for TypedUpid<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: UpidPrefix> Ord for TypedUpid<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.upid.cmp(&other.upid)
+    }
+}
+
+impl<P: UpidPrefix> Hash for TypedUpid<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.upid.hash(state)
+    }
+}
+
+impl<P: UpidPrefix> fmt::Debug for TypedUpid<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedUpid").field(&self.upid).finish()
+    }
+}
+
+impl<P: UpidPrefix> fmt::Display for TypedUpid<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.upid, f)
+    }
+}
+
+impl<P: UpidPrefix> FromStr for TypedUpid<P> {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TypedUpid::from_string(s)
+    }
+}
+
+impl<P: UpidPrefix> From<TypedUpid<P>> for Upid {
+    fn from(typed: TypedUpid<P>) -> Upid {
+        typed.upid
+    }
+}
+
+impl<P: UpidPrefix> TryFrom<Upid> for TypedUpid<P> {
+    type Error = DecodeError;
+
+    fn try_from(upid: Upid) -> Result<Self, Self::Error> {
+        TypedUpid::from_upid(upid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct User;
+
+    impl UpidPrefix for User {
+        const PREFIX: &'static str = "user";
+    }
+
+    #[test]
+    fn test_typed() {
+        let id = TypedUpid::<User>::new();
+        let text = id.to_string();
+
+        assert_eq!(text.parse::<TypedUpid<User>>(), Ok(id));
+        assert_eq!(Upid::from(id).prefix(), "user");
+        assert_eq!(
+            TypedUpid::<User>::try_from(Upid::new("ordr")),
+            Err(DecodeError::UnexpectedPrefix)
+        );
+        assert_eq!(
+            TypedUpid::<User>::from_string("user"),
+            Err(DecodeError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Convert between Upid and Ulid, for migrating ULID-keyed systems.
+//!
+//! Unlike the `uuid` conversions, these map the fields rather than the bits:
+//! - the ULID's 48-bit millisecond timestamp is truncated to the Upid's 256ms precision
+//! - the top 64 of the ULID's 80 random bits become the Upid's random bits,
+//!   and the top 64 random bits of a Upid of any version go back
+//! - the prefix is chosen by the caller (or 'zzzz' for `From<Ulid>`)
+//!
+//! Going back with [`Upid::to_ulid`] loses the prefix, and the dropped time
+//! and random bits come back as zeroes, so a ULID only survives a round trip
+//! if those bits were zero to begin with. Both directions keep the sort order by time.
+
+use ulid::Ulid;
+
+use crate::{from_parts, prefix_bits, Upid};
+
+impl Upid {
+    /// Creates a Upid with the time and randomness of a ULID, and the given prefix
+    ///
+    /// The prefix is handled the same way as in [`Upid::from_prefix`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use ulid::Ulid;
+    /// use upid::Upid;
+    ///
+    /// let ulid = Ulid::from_parts(1720600366848, 0xabcd_ef01_2345_6789_0000);
+    /// let upid = Upid::from_ulid(ulid, "user");
+    ///
+    /// assert_eq!(upid.prefix(), "user");
+    /// assert_eq!(upid.milliseconds(), 1720600366848);
+    /// assert_eq!(upid.to_ulid(), ulid);
+    /// ```
+    pub fn from_ulid(ulid: Ulid, prefix: &str) -> Upid {
+        let time_bits = (ulid.timestamp_ms() as u128) >> 8;
+        let random = ulid.random() >> 16;
+        from_parts(time_bits, random, prefix_bits(prefix))
+    }
+
+    /// Converts the Upid to a ULID with the same time and randomness
+    ///
+    /// This is lossy: the prefix is dropped, and the low 16 random bits of the ULID are zero.
+    pub fn to_ulid(&self) -> Ulid {
+        let random = self.random_u64() as u128;
+        Ulid::from_parts(self.milliseconds(), random << 16)
+    }
+}
+
+/// Maps the fields with an empty prefix, see [`Upid::from_ulid`]
+impl From<Ulid> for Upid {
+    fn from(ulid: Ulid) -> Self {
+        Upid::from_ulid(ulid, "")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::UpidBuilder;
+
+    #[test]
+    fn ulid_fields() {
+        let ulid = Ulid::from_parts(1720600366900, (0x1234_5678_9abc_def0 << 16) | 0xffff);
+        let upid = Upid::from_ulid(ulid, "user");
+
+        assert_eq!(upid.prefix(), "user");
+        assert_eq!(upid.milliseconds(), 1720600366848);
+        assert_eq!((upid.0 >> 24) as u64, 0x1234_5678_9abc_def0);
+        assert_eq!(Upid::from(ulid).prefix(), "zzzz");
+
+        let back = upid.to_ulid();
+        assert_eq!(back.timestamp_ms(), 1720600366848);
+        assert_eq!(back.random(), 0x1234_5678_9abc_def0 << 16);
+    }
+
+    #[test]
+    fn ulid_v2() {
+        let order = |prefix, random| {
+            UpidBuilder::new()
+                .prefix(prefix)
+                .version('b')
+                .milliseconds(1720600366848)
+                .random(random)
+                .build()
+        };
+        let upid = order("order", 0x70_435c_61c9_9524);
+
+        assert_eq!(upid.to_ulid().random(), 0x7043_5c61_c995_2400 << 16);
+        // the prefix isn't mixed into the randomness
+        assert_eq!(
+            upid.to_ulid(),
+            order("other", 0x70_435c_61c9_9524).to_ulid()
+        );
+        assert_ne!(
+            upid.to_ulid(),
+            order("order", 0x70_435c_61c9_9525).to_ulid()
+        );
+
+        let long = Upid::new_v2("");
+        assert_eq!(long.to_ulid().random() >> 16, long.random() >> 17);
+    }
+
+    #[test]
+    fn ulid_cycle() {
+        let upid = Upid::new("user");
+        let got = Upid::from_ulid(upid.to_ulid(), "user");
+
+        assert_eq!(got, upid);
+    }
+
+    #[test]
+    fn ulid_order() {
+        let earlier = Ulid::from_parts(1720600366848, u64::MAX as u128);
+        let later = Ulid::from_parts(1720600366848 + 256, 0);
+
+        assert!(Upid::from(earlier) < Upid::from(later));
+    }
+}
@@ -0,0 +1,91 @@
+//! Describe Upids in OpenAPI documents generated by `utoipa`.
+//!
+//! Upids are documented as strings with a `upid` format, a pattern
+//! that matches every supported version, and an example.
+//! As a parameter, a bare Upid is named `id` and is in the path by default.
+
+use std::borrow::Cow;
+
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::schema::{ObjectBuilder, Schema, SchemaFormat, Type};
+use utoipa::openapi::{RefOr, Required};
+use utoipa::{IntoParams, PartialSchema, ToSchema};
+
+use crate::Upid;
+
+/// Matches the string form of every supported version
+const PATTERN: &str = "^([2-7a-z]{4}_[2-7a-z]{21}[ac]|[2-7a-z]{0,5}_[2-7a-z]{20,25}b)$";
+
+const EXAMPLE: &str = "user_2accvpp5guht4dts56je5a";
+
+impl PartialSchema for Upid {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .format(Some(SchemaFormat::Custom("upid".into())))
+            .pattern(Some(PATTERN))
+            .min_length(Some(27))
+            .max_length(Some(27))
+            .description(Some("Prefixed, sortable, 128-bit identifier"))
+            .examples([EXAMPLE])
+            .into()
+    }
+}
+
+impl ToSchema for Upid {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Upid")
+    }
+}
+
+impl IntoParams for Upid {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![ParameterBuilder::new()
+            .name("id")
+            .parameter_in(parameter_in_provider().unwrap_or(ParameterIn::Path))
+            .required(Required::True)
+            .schema(Some(Upid::schema()))
+            .build()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schema() {
+        let schema = serde_json::to_value(Upid::schema()).unwrap();
+
+        assert_eq!(schema["type"], "string");
+        assert_eq!(schema["format"], "upid");
+        assert_eq!(schema["examples"][0], EXAMPLE);
+        assert_eq!(Upid::name(), "Upid");
+    }
+
+    #[test]
+    fn pattern_matches() {
+        let pattern = regex_lite::Regex::new(PATTERN).unwrap();
+        for upid in [
+            Upid::new("user"),
+            Upid::new_v2(""),
+            Upid::new_v2("order"),
+            Upid::new_v3("post"),
+        ] {
+            assert!(pattern.is_match(&upid.to_string()), "{upid}");
+        }
+        assert!(pattern.is_match(EXAMPLE));
+        assert!(!pattern.is_match("USER_2ACCVPP5GUHT4DTS56JE5A"));
+    }
+
+    #[test]
+    fn params() {
+        let params = Upid::into_params(|| None);
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].name, "id");
+        assert!(params[0].parameter_in == ParameterIn::Path);
+
+        let params = Upid::into_params(|| Some(ParameterIn::Query));
+        assert!(params[0].parameter_in == ParameterIn::Query);
+    }
+}
@@ -1,7 +1,87 @@
 //! Convert between Upid and Uuid.
+//!
+//! `From`/`Into` reinterpret the bits, which round-trips exactly but doesn't produce
+//! a valid UUID of any version. [`Upid::to_uuid_v7`] and [`Upid::from_uuid_v7`]
+//! instead move the fields into the UUIDv7 layout, so the UUID sorts by time in
+//! UUID-native systems:
+//!
+//! ```text
+//! unix_ts_ms (48) = upid time (40) | random bits 63-56 (8)
+//! ver (4)         = 7
+//! rand_a (12)     = random bits 55-44
+//! var (2)         = 0b10
+//! rand_b (62)     = random bits 43-2 (42) | prefix (20)
+//! ```
+//!
+//! Stashing 8 random bits in the timestamp keeps it within the Upid's own 256ms window.
+//! The version and variant take 6 bits, so the two lowest random bits and the version
+//! character are dropped. Only the `a` layout fits, so other versions aren't converted,
+//! and the two dropped bits come back as zero.
 
-use crate::Upid;
-use uuid::Uuid;
+use uuid::{Uuid, Variant};
+
+use crate::{b32, Upid};
+
+/// Mask of the prefix bits, without the version
+const PREFIX_MASK: u128 = 0xf_ffff;
+
+impl Upid {
+    /// Converts to a valid UUIDv7 that sorts by the same time as the Upid
+    ///
+    /// This drops the two lowest random bits, see the module docs.
+    /// Returns `None` for versions other than `a`, which can't be converted back.
+    ///
+    /// # Example
+    /// ```rust
+    /// use upid::Upid;
+    ///
+    /// let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+    /// let uuid = upid.to_uuid_v7().unwrap();
+    ///
+    /// assert_eq!(uuid.get_version_num(), 7);
+    /// assert_eq!(Upid::from_uuid_v7(uuid), Some(upid));
+    /// assert_eq!(Upid::new_v2("tx").to_uuid_v7(), None);
+    /// ```
+    pub fn to_uuid_v7(&self) -> Option<Uuid> {
+        if self.0 & 0xf != b32::VERSION_V1 as u128 {
+            return None;
+        }
+        let time = self.0 >> 88;
+        let random = (self.0 >> 24) as u64 as u128;
+        let prefix = (self.0 >> 4) & PREFIX_MASK;
+
+        let unix_ts_ms = (time << 8) | (random >> 56);
+        let rand_a = (random >> 44) & 0xfff;
+        let rand_b = (((random >> 2) & ((1 << 42) - 1)) << 20) | prefix;
+
+        Some(Uuid::from_u128(
+            (unix_ts_ms << 80) | (0x7 << 76) | (rand_a << 64) | (0b10 << 62) | rand_b,
+        ))
+    }
+
+    /// Converts back from a UUID made by [`Upid::to_uuid_v7`]
+    ///
+    /// Returns `None` if the UUID isn't version 7 with the RFC variant.
+    /// Any UUIDv7 is accepted, but one that didn't come from a Upid
+    /// will have an arbitrary prefix.
+    pub fn from_uuid_v7(uuid: Uuid) -> Option<Upid> {
+        if uuid.get_version_num() != 7 || uuid.get_variant() != Variant::RFC4122 {
+            return None;
+        }
+        let bits = uuid.as_u128();
+        let unix_ts_ms = bits >> 80;
+        let rand_a = (bits >> 64) & 0xfff;
+        let rand_b = bits & ((1 << 62) - 1);
+
+        let time = unix_ts_ms >> 8;
+        let random = ((unix_ts_ms & 0xff) << 56) | (rand_a << 44) | ((rand_b >> 20) << 2);
+        let prefix = rand_b & PREFIX_MASK;
+
+        Some(Upid(
+            (time << 88) | (random << 24) | (prefix << 4) | b32::VERSION_V1 as u128,
+        ))
+    }
+}
 
 impl From<Uuid> for Upid {
     fn from(uuid: Uuid) -> Self {
@@ -27,4 +107,45 @@ mod test {
 
         assert_eq!(got, want)
     }
+
+    #[test]
+    fn uuid_v7_cycle() {
+        for _ in 0..100 {
+            let want = Upid::new("user");
+            let uuid = want.to_uuid_v7().unwrap();
+
+            assert_eq!(uuid.get_version_num(), 7);
+            assert_eq!(uuid.get_variant(), Variant::RFC4122);
+            // the lowest two random bits are dropped
+            assert_eq!(Upid::from_uuid_v7(uuid), Some(Upid(want.0 & !(0b11 << 24))));
+        }
+    }
+
+    #[test]
+    fn uuid_v7_time() {
+        let upid = Upid::from_prefix_and_milliseconds("user", 1720600366848);
+        let uuid = upid.to_uuid_v7().unwrap();
+        let (seconds, nanos) = uuid.get_timestamp().unwrap().to_unix();
+        let ms = seconds * 1000 + nanos as u64 / 1_000_000;
+
+        assert!((1720600366848..1720600366848 + 256).contains(&ms));
+        let later = Upid::from_prefix_and_milliseconds("aaaa", 1720600366848 + 256);
+        assert!(uuid < later.to_uuid_v7().unwrap());
+    }
+
+    #[test]
+    fn uuid_v7_rejects_other_versions() {
+        let upid = Upid::from_string("user_2acdrlkjmhs6ar53taem6a").unwrap();
+        assert_eq!(Upid::from_uuid_v7(upid.into()), None);
+        assert_eq!(Upid::from_uuid_v7(Uuid::nil()), None);
+    }
+
+    #[test]
+    fn uuid_v7_only_from_version_a() {
+        assert_eq!(Upid::new_v2("tx").to_uuid_v7(), None);
+        assert_eq!(Upid::new_v2("order").to_uuid_v7(), None);
+        // the time of a version `c` Upid counts from a different epoch
+        assert_eq!(Upid::new_v3("post").to_uuid_v7(), None);
+        assert_eq!(Upid(u128::MAX).to_uuid_v7(), None);
+    }
 }
@@ -0,0 +1,99 @@
+//! Wipe Upids from memory, for deployments that treat some IDs as secrets.
+//!
+//! [`Upid`] is `Copy`, so it can't clean up after itself on drop, and copies of it
+//! can be left anywhere. It implements [`Zeroize`] for wiping by hand, and
+//! [`SecretUpid`] wraps one that is wiped when dropped, as is its string form
+//! and the buffer it was encoded in.
+//!
+//! ```rust
+//! use upid::SecretUpid;
+//!
+//! let token = SecretUpid::new("rset");
+//! let link = format!("https://example.com/reset/{}", token.to_string().as_str());
+//! ```
+
+use std::fmt;
+
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{b32, DecodeError, Upid};
+
+impl Zeroize for Upid {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// A Upid that is wiped from memory when dropped
+///
+/// It is deliberately not `Copy` or `Display`, and its `Debug` output is redacted.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct SecretUpid(Upid);
+
+impl SecretUpid {
+    /// Creates a new secret Upid with the provided prefix and current time (UTC)
+    pub fn new(prefix: &str) -> SecretUpid {
+        SecretUpid(Upid::from_prefix(prefix))
+    }
+
+    /// Parses a secret Upid
+    ///
+    /// The caller is responsible for wiping `encoded`.
+    pub fn from_string(encoded: &str) -> Result<SecretUpid, DecodeError> {
+        Upid::from_string(encoded).map(SecretUpid)
+    }
+
+    /// Gets the string representation, which is wiped when dropped
+    #[allow(clippy::inherent_to_string)] // no Display, so the secret isn't logged by accident
+    pub fn to_string(&self) -> Zeroizing<String> {
+        let mut buffer = Zeroizing::new([0; b32::STR_LEN]);
+        let text = self.0.encode_to(&mut buffer);
+        let mut out = Zeroizing::new(String::with_capacity(b32::STR_LEN));
+        out.push_str(text);
+        out
+    }
+
+    /// Gets the wrapped Upid, as a copy that won't be wiped
+    pub fn expose(&self) -> Upid {
+        self.0
+    }
+}
+
+impl From<Upid> for SecretUpid {
+    fn from(upid: Upid) -> SecretUpid {
+        SecretUpid(upid)
+    }
+}
+
+impl fmt::Debug for SecretUpid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretUpid(..)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn zeroize_upid() {
+        let mut upid = Upid::from_string(TEXT).unwrap();
+        upid.zeroize();
+        assert_eq!(upid, Upid(0));
+    }
+
+    #[test]
+    fn secret_cycle() {
+        let secret = SecretUpid::from_string(TEXT).unwrap();
+
+        assert_eq!(secret.to_string().as_str(), TEXT);
+        assert_eq!(secret.expose().prefix(), "user");
+        assert_eq!(format!("{secret:?}"), "SecretUpid(..)");
+
+        let mut secret = secret;
+        secret.zeroize();
+        assert_eq!(secret.expose(), Upid(0));
+    }
+}
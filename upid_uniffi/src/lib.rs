@@ -0,0 +1,130 @@
+//! # upid_uniffi
+//!
+//! `upid_uniffi` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! exposing UPID generation and parsing to Kotlin and Swift through UniFFI,
+//! so mobile apps mint and validate IDs with the same semantics as the backend.
+//!
+//! Generate the bindings from the built library with `uniffi-bindgen generate`.
+
+use std::fmt;
+use std::sync::Arc;
+
+use inner_upid::{DecodeError, Upid as InnerUpid};
+
+uniffi::setup_scaffolding!();
+
+/// The reasons a string can't be parsed as a Upid
+#[derive(Debug, PartialEq, Eq, Clone, Copy, uniffi::Error)]
+pub enum UpidError {
+    InvalidLength,
+    InvalidChar,
+    Overflow,
+    UnsupportedVersion,
+    UnexpectedPrefix,
+    Invalid,
+}
+
+impl fmt::Display for UpidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for UpidError {}
+
+impl From<DecodeError> for UpidError {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::InvalidLength => UpidError::InvalidLength,
+            DecodeError::InvalidChar => UpidError::InvalidChar,
+            DecodeError::Overflow => UpidError::Overflow,
+            DecodeError::UnsupportedVersion(_) => UpidError::UnsupportedVersion,
+            DecodeError::UnexpectedPrefix => UpidError::UnexpectedPrefix,
+            _ => UpidError::Invalid,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, uniffi::Object)]
+pub struct Upid(InnerUpid);
+
+#[uniffi::export]
+impl Upid {
+    /// Creates a new Upid with the provided prefix and current time
+    #[uniffi::constructor]
+    pub fn new(prefix: String) -> Arc<Upid> {
+        Arc::new(Upid(InnerUpid::from_prefix(&prefix)))
+    }
+
+    /// Parses a Upid from its string form
+    #[uniffi::constructor]
+    pub fn parse(text: String) -> Result<Arc<Upid>, UpidError> {
+        Ok(Arc::new(Upid(InnerUpid::from_string(&text)?)))
+    }
+
+    /// Creates a Upid from its 16 big-endian bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Upid>, UpidError> {
+        Ok(Arc::new(Upid(InnerUpid::from_slice(&bytes)?)))
+    }
+
+    /// Returns the string form of this Upid
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Returns the 16 big-endian bytes of this Upid
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+
+    /// Returns the timestamp of this Upid in milliseconds since the Unix epoch
+    pub fn timestamp_ms(&self) -> u64 {
+        self.0.milliseconds()
+    }
+
+    /// Returns the prefix of this Upid
+    pub fn prefix(&self) -> String {
+        self.0.prefix()
+    }
+
+    /// Returns the spec version character of this Upid
+    pub fn version(&self) -> String {
+        self.0.version().to_string()
+    }
+}
+
+/// Checks whether a string is a valid Upid, without creating one
+#[uniffi::export]
+pub fn is_valid(text: String) -> bool {
+    InnerUpid::validate(&text).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+
+    #[test]
+    fn test_cycle() {
+        let upid = Upid::parse(TEXT.to_string()).unwrap();
+        assert_eq!(upid.to_string(), TEXT);
+        assert_eq!(upid.prefix(), "user");
+        assert_eq!(upid.version(), "a");
+        assert_eq!(upid.timestamp_ms(), 1720600366848);
+        assert_eq!(Upid::from_bytes(upid.to_bytes()), Ok(upid));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Upid::parse("user_short".to_string()),
+            Err(UpidError::InvalidLength)
+        );
+        assert_eq!(Upid::from_bytes(vec![0; 15]), Err(UpidError::InvalidLength));
+        assert!(is_valid(TEXT.to_string()));
+        assert!(!is_valid("user_2acdrlkjmhs6ar53taem6z".to_string()));
+    }
+}
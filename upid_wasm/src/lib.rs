@@ -0,0 +1,88 @@
+//! # upid_wasm
+//!
+//! `upid_wasm` is a thin wrapper for [upid](https://crates.io/crates/upid)
+//! providing UPID generation and parsing to JavaScript and TypeScript via wasm-bindgen.
+//!
+//! The bit layout is exactly the same as the Rust implementation, so IDs can
+//! be passed freely between the browser and the backend.
+
+use inner_upid::Upid as InnerUpid;
+use uuid::Uuid;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Upid(InnerUpid);
+
+#[wasm_bindgen]
+impl Upid {
+    /// Creates a new Upid with the provided prefix and current time
+    #[wasm_bindgen(constructor)]
+    pub fn new(prefix: &str) -> Upid {
+        Upid(InnerUpid::from_prefix(prefix))
+    }
+
+    /// Parses a Upid from its string form, throwing an `Error` if invalid
+    pub fn parse(text: &str) -> Result<Upid, JsError> {
+        InnerUpid::from_string(text)
+            .map(Upid)
+            .map_err(|err| JsError::new(&format!("invalid upid: \"{text}\": {err}")))
+    }
+
+    /// Returns the string form of this Upid
+    #[allow(clippy::inherent_to_string)]
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Returns the hyphenated UUID form of this Upid
+    #[wasm_bindgen(js_name = toUuid)]
+    pub fn to_uuid(&self) -> String {
+        Uuid::from(self.0).hyphenated().to_string()
+    }
+
+    /// Returns the timestamp of this Upid in milliseconds since the Unix epoch
+    #[wasm_bindgen(js_name = timestampMs)]
+    pub fn timestamp_ms(&self) -> f64 {
+        self.0.milliseconds() as f64
+    }
+
+    /// Returns the prefix of this Upid
+    #[wasm_bindgen(getter)]
+    pub fn prefix(&self) -> String {
+        self.0.prefix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "user_2acdrlkjmhs6ar53taem6a";
+    const UUID: &str = "01909bc6-0f93-7043-5c61-c99524d61576";
+
+    // JsError can only be constructed on wasm targets, so avoid Upid::parse here
+    fn upid() -> Upid {
+        Upid(InnerUpid::from_string(TEXT).unwrap())
+    }
+
+    #[test]
+    fn test_cycle() {
+        let upid = upid();
+        assert_eq!(upid.to_string(), TEXT);
+        assert_eq!(upid.prefix(), "user");
+    }
+
+    #[test]
+    fn test_to_uuid() {
+        let upid = upid();
+        assert_eq!(upid.to_uuid(), UUID);
+    }
+
+    #[test]
+    fn test_timestamp_ms() {
+        let upid = upid();
+        assert_eq!(upid.timestamp_ms(), 1720600366848.0);
+    }
+}